@@ -0,0 +1,45 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use pubport::descriptor::Descriptors;
+
+const DESCRIPTOR: &str = "wpkh([817e7be0/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>/*)#60tjs4c7";
+const COUNT: usize = 10_000;
+
+fn parse_descriptors(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse 10k descriptors");
+    group.sample_size(10);
+
+    group.bench_function("new context per descriptor", |b| {
+        b.iter(|| {
+            for _ in 0..COUNT {
+                let secp = secp256k1::Secp256k1::signing_only();
+                let desc = Descriptors::try_from_line_with_secp(black_box(DESCRIPTOR), &secp);
+                black_box(desc.unwrap());
+            }
+        })
+    });
+
+    group.bench_function("shared context", |b| {
+        let secp = secp256k1::Secp256k1::signing_only();
+
+        b.iter(|| {
+            for _ in 0..COUNT {
+                let desc = Descriptors::try_from_line_with_secp(black_box(DESCRIPTOR), &secp);
+                black_box(desc.unwrap());
+            }
+        })
+    });
+
+    group.bench_function("try_from_line", |b| {
+        b.iter(|| {
+            for _ in 0..COUNT {
+                let desc = Descriptors::try_from_line(black_box(DESCRIPTOR));
+                black_box(desc.unwrap());
+            }
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, parse_descriptors);
+criterion_main!(benches);
@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pubport::formats::Format;
+
+fuzz_target!(|data: &[u8]| {
+    let string = String::from_utf8_lossy(data);
+    let _ = Format::try_new_from_str(&string);
+});
@@ -0,0 +1,234 @@
+use serde::{Deserialize, Serialize};
+
+use crate::descriptor::{self, Descriptors};
+
+/// The only BSMS version there is so far
+const VERSION: &str = "1.0";
+
+/// Written in the path restrictions line when the wallet allows any path
+const NO_PATH_RESTRICTIONS: &str = "No path restrictions";
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Not a BSMS file, it doesn't start with `BSMS 1.0`")]
+    MissingHeader,
+
+    #[error("Unsupported BSMS version: {0}")]
+    UnsupportedVersion(String),
+
+    #[error("BSMS file is encrypted, decrypt it with the token from the coordinator first")]
+    Encrypted,
+
+    #[error(
+        "BSMS key record only has one signer's key, import the coordinator's descriptor record"
+    )]
+    KeyRecord,
+
+    #[error("BSMS descriptor record is missing the {0} line")]
+    MissingLine(&'static str),
+
+    #[error("Invalid BSMS path restrictions, expected `/0/*,/1/*`: {0}")]
+    InvalidPathRestrictions(String),
+
+    #[error("Invalid descriptor in BSMS file: {0}")]
+    InvalidDescriptor(#[from] descriptor::Error),
+}
+
+/// A BIP129 descriptor record, the wallet file the BSMS coordinator sends to every signer
+///
+/// ```text
+/// BSMS 1.0
+/// wsh(sortedmulti(2,[817e7be0/48h/0h/0h/2h]xpub.../**,[90645a28/48h/0h/0h/2h]xpub.../**))
+/// /0/*,/1/*
+/// bc1q...
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Object))]
+pub struct Bsms {
+    pub version: String,
+
+    /// Descriptor template, the keys end in `/**` which the path restrictions fill in
+    pub template: String,
+
+    /// `/0/*,/1/*` for the receive and change keychains, or `No path restrictions`
+    pub path_restrictions: String,
+
+    /// The first receive address, checked against the descriptor when importing
+    pub first_address: String,
+}
+
+impl Bsms {
+    /// Parse a plaintext descriptor record, files encrypted with a token are rejected
+    ///
+    /// A record made with the `00` token is not encrypted and is read like any other.
+    pub fn try_from_str(string: &str) -> Result<Self, Error> {
+        let string = string.trim();
+
+        if !string.is_empty() && string.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(Error::Encrypted);
+        }
+
+        let mut lines = string
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty());
+
+        let version = lines
+            .next()
+            .and_then(|line| line.strip_prefix("BSMS "))
+            .ok_or(Error::MissingHeader)?;
+
+        if version != VERSION {
+            return Err(Error::UnsupportedVersion(version.to_string()));
+        }
+
+        let template = lines.next().ok_or(Error::MissingLine("descriptor"))?;
+
+        // a key record has the token on the second line, then the signer's key
+        if !template.contains('(') {
+            return Err(Error::KeyRecord);
+        }
+
+        let path_restrictions = lines
+            .next()
+            .ok_or(Error::MissingLine("path restrictions"))?;
+        let first_address = lines.next().ok_or(Error::MissingLine("first address"))?;
+
+        Ok(Self {
+            version: version.to_string(),
+            template: template.to_string(),
+            path_restrictions: path_restrictions.to_string(),
+            first_address: first_address.to_string(),
+        })
+    }
+
+    /// The descriptors for the wallet, with `/**` expanded from the path restrictions
+    ///
+    /// Fails if the first address in the record isn't the one the descriptor derives.
+    pub fn to_descriptors(&self) -> Result<Descriptors, Error> {
+        let multipath = match self.path_restrictions.as_str() {
+            NO_PATH_RESTRICTIONS => "/<0;1>/*".to_string(),
+            restrictions => {
+                let invalid = || Error::InvalidPathRestrictions(restrictions.to_string());
+                let index = |path: &str| {
+                    path.strip_prefix('/')
+                        .and_then(|path| path.strip_suffix("/*"))
+                        .and_then(|index| index.parse::<u32>().ok())
+                        .ok_or_else(invalid)
+                };
+
+                let (receive, change) = restrictions.split_once(',').ok_or_else(invalid)?;
+                format!("/<{};{}>/*", index(receive)?, index(change)?)
+            }
+        };
+
+        // the checksum is for the template, not the expanded descriptor
+        let template = match self.template.split_once('#') {
+            Some((template, _checksum)) => template,
+            None => &self.template,
+        };
+
+        let descriptors = Descriptors::try_from_line(&template.replace("/**", &multipath))?;
+        descriptors.verify_first_address(&self.first_address)?;
+
+        Ok(descriptors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record() -> String {
+        std::fs::read_to_string("test/data/bsms-descriptor-record.txt").unwrap()
+    }
+
+    #[test]
+    fn test_parse_descriptor_record() {
+        let bsms = Bsms::try_from_str(&record()).unwrap();
+
+        assert_eq!(bsms.version, "1.0");
+        assert!(bsms
+            .template
+            .starts_with("wsh(sortedmulti(2,[817e7be0/48h/0h/0h/2h]"));
+        assert_eq!(bsms.path_restrictions, "/0/*,/1/*");
+
+        let descriptors = bsms.to_descriptors().unwrap();
+        let expected = Descriptors::try_from_line(&bsms.template.replace("/**", "/<0;1>/*"));
+        assert_eq!(descriptors, expected.unwrap());
+        assert_eq!(descriptors.cosigner_count(), 2);
+    }
+
+    #[test]
+    fn test_no_path_restrictions() {
+        let mut bsms = Bsms::try_from_str(&record()).unwrap();
+        bsms.path_restrictions = NO_PATH_RESTRICTIONS.to_string();
+        assert!(bsms.to_descriptors().is_ok());
+
+        bsms.path_restrictions = "/0/*".to_string();
+        assert!(matches!(
+            bsms.to_descriptors(),
+            Err(Error::InvalidPathRestrictions(_))
+        ));
+    }
+
+    #[test]
+    fn test_first_address_mismatch() {
+        let mut bsms = Bsms::try_from_str(&record()).unwrap();
+
+        // the first change address
+        bsms.first_address =
+            "bc1quh759rgsm4rqcex2meratj0kzj7s4gjxx9tvsplrav6tpz5m07xstmaagd".to_string();
+        assert!(matches!(
+            bsms.to_descriptors(),
+            Err(Error::InvalidDescriptor(
+                descriptor::Error::FirstAddressMismatch { .. }
+            ))
+        ));
+
+        // same script, but a testnet address
+        bsms.first_address =
+            "tb1qpj7q2ad4tt4zdqa8nh8cw9p0x9p5c27rwyg3smp2nvqefsc447lqae55sj".to_string();
+        assert!(bsms.to_descriptors().is_ok());
+
+        bsms.first_address = "not an address".to_string();
+        assert!(matches!(
+            bsms.to_descriptors(),
+            Err(Error::InvalidDescriptor(descriptor::Error::InvalidAddress(
+                _
+            )))
+        ));
+    }
+
+    #[test]
+    fn test_rejected_records() {
+        let encrypted = "1a2b3c4d5e6f7a8b9c0d1e2f3a4b5c6d7e8f9a0b1c2d3e4f5a6b7c8d9e0f1a2b";
+        assert!(matches!(
+            Bsms::try_from_str(encrypted),
+            Err(Error::Encrypted)
+        ));
+
+        let key_record = "BSMS 1.0\n00\n[817e7be0/48h/0h/0h/2h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM\nSigner 1\nsignature";
+        assert!(matches!(
+            Bsms::try_from_str(key_record),
+            Err(Error::KeyRecord)
+        ));
+
+        let version = record().replace("BSMS 1.0", "BSMS 2.0");
+        assert!(matches!(
+            Bsms::try_from_str(&version),
+            Err(Error::UnsupportedVersion(_))
+        ));
+
+        assert!(matches!(
+            Bsms::try_from_str("wpkh(xpub)"),
+            Err(Error::MissingHeader)
+        ));
+
+        let truncated = record().lines().take(3).collect::<Vec<_>>().join("\n");
+        assert!(matches!(
+            Bsms::try_from_str(&truncated),
+            Err(Error::MissingLine("first address"))
+        ));
+    }
+}
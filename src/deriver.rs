@@ -0,0 +1,146 @@
+use std::num::NonZeroUsize;
+
+use bitcoin::{Address, Network};
+use lru::LruCache;
+use miniscript::{Descriptor, DescriptorPublicKey};
+
+use crate::descriptor::{check_index, Descriptors, Error, Keychain, MAX_DERIVATION_INDEX};
+
+/// Default number of unused addresses a scanner looks ahead on each keychain
+pub const DEFAULT_GAP_LIMIT: usize = 20;
+
+/// Derives addresses from [`Descriptors`], reusing one secp context and caching recent addresses
+///
+/// [`Descriptors::address_at`] is fine for a handful of addresses, but it sets everything up
+/// again on every call. Scanners deriving thousands of addresses should use this instead.
+pub struct DescriptorDeriver {
+    secp: secp256k1::Secp256k1<secp256k1::VerifyOnly>,
+    external: Descriptor<DescriptorPublicKey>,
+    internal: Descriptor<DescriptorPublicKey>,
+    network: Network,
+    max_index: u32,
+    cache: LruCache<(Keychain, u32), Address>,
+}
+
+impl DescriptorDeriver {
+    pub fn new(descriptors: &Descriptors, network: Network) -> Self {
+        let gap_limit = NonZeroUsize::new(DEFAULT_GAP_LIMIT).expect("non zero");
+        Self::with_gap_limit(descriptors, network, gap_limit)
+    }
+
+    /// Cache enough addresses to cover a window of `gap_limit` addresses on both keychains
+    pub fn with_gap_limit(
+        descriptors: &Descriptors,
+        network: Network,
+        gap_limit: NonZeroUsize,
+    ) -> Self {
+        let cache_size = gap_limit.saturating_mul(NonZeroUsize::new(2).expect("non zero"));
+
+        Self {
+            secp: secp256k1::Secp256k1::verification_only(),
+            external: descriptors.external.clone(),
+            internal: descriptors.internal.clone(),
+            network,
+            max_index: MAX_DERIVATION_INDEX,
+            cache: LruCache::new(cache_size),
+        }
+    }
+
+    /// Refuse to derive past `max_index`, capped at [`MAX_DERIVATION_INDEX`]
+    ///
+    /// Useful to stop a runaway scan well before the end of the keychain.
+    pub fn with_max_index(mut self, max_index: u32) -> Self {
+        self.max_index = max_index.min(MAX_DERIVATION_INDEX);
+        self
+    }
+
+    pub fn derive_address(&mut self, keychain: Keychain, index: u32) -> Result<Address, Error> {
+        check_index(index, self.max_index)?;
+
+        if let Some(address) = self.cache.get(&(keychain, index)) {
+            return Ok(address.clone());
+        }
+
+        let descriptor = match keychain {
+            Keychain::External => &self.external,
+            Keychain::Internal => &self.internal,
+        };
+
+        let address = descriptor
+            .derived_descriptor(&self.secp, index)?
+            .address(self.network)
+            .map_err(Error::UnableToGetAddress)?;
+
+        self.cache.put((keychain, index), address.clone());
+        Ok(address)
+    }
+
+    /// Number of addresses currently cached
+    pub fn cached(&self) -> usize {
+        self.cache.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn known_desc() -> Descriptors {
+        let known_desc = "wpkh([817e7be0/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>/*)#60tjs4c7";
+        Descriptors::try_from_line(known_desc).unwrap()
+    }
+
+    #[test]
+    fn test_derive_first_address() {
+        let mut deriver = DescriptorDeriver::new(&known_desc(), Network::Bitcoin);
+        let address = deriver.derive_address(Keychain::External, 0).unwrap();
+
+        assert_eq!(
+            address.to_string(),
+            "bc1q0g0vn4yqyk0zjwxw0zv5pltyyczty004zc9g7r"
+        );
+    }
+
+    #[test]
+    fn test_matches_address_at() {
+        let desc = known_desc();
+        let mut deriver = DescriptorDeriver::new(&desc, Network::Bitcoin);
+
+        for keychain in Keychain::ALL {
+            for index in 0..5 {
+                let address = deriver.derive_address(keychain, index).unwrap();
+                let expected = desc.address_at(keychain, index, Network::Bitcoin).unwrap();
+                assert_eq!(address, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cache_is_bounded() {
+        let gap_limit = NonZeroUsize::new(2).unwrap();
+        let mut deriver =
+            DescriptorDeriver::with_gap_limit(&known_desc(), Network::Bitcoin, gap_limit);
+
+        for index in 0..10 {
+            deriver.derive_address(Keychain::External, index).unwrap();
+        }
+
+        assert_eq!(deriver.cached(), 4);
+    }
+
+    #[test]
+    fn test_max_index() {
+        let mut deriver = DescriptorDeriver::new(&known_desc(), Network::Bitcoin);
+        assert!(matches!(
+            deriver.derive_address(Keychain::External, MAX_DERIVATION_INDEX + 1),
+            Err(Error::IndexOutOfRange(_))
+        ));
+
+        let mut deriver = deriver.with_max_index(100);
+        assert!(deriver.derive_address(Keychain::External, 100).is_ok());
+        assert!(matches!(
+            deriver.derive_address(Keychain::Internal, 101),
+            Err(Error::IndexOutOfRange(101))
+        ));
+    }
+}
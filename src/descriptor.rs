@@ -1,12 +1,54 @@
-use bitcoin::bip32::Fingerprint;
-use miniscript::{descriptor::DescriptorKeyParseError, Descriptor, DescriptorPublicKey};
+use std::{borrow::Cow, str::FromStr as _};
+
+use bitcoin::{
+    address::NetworkUnchecked,
+    bip32::{ChildNumber, DerivationPath, Fingerprint},
+    sign_message::{signed_msg_hash, MessageSignature, MessageSignatureError},
+    Address, CompressedPublicKey, Network, NetworkKind, ScriptBuf,
+};
+use miniscript::{
+    descriptor::{
+        checksum::desc_checksum, ConversionError, DerivPaths, DescriptorKeyParseError,
+        DescriptorMultiXKey, ShInner, SinglePub, SinglePubKey, Wildcard,
+    },
+    translate_hash_clone, Descriptor, DescriptorPublicKey, ForEachKey as _, TranslateErr,
+    TranslatePk as _, Translator,
+};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    json::{ElectrumJson, Name, SingleSig, WasabiJson},
-    xpub,
+    json::{
+        Bitbox02Json, BitboxSimple, BitcoinCoreJson, ElectrumJson, Keystore, LedgerLiveJson,
+        SingleSig, SparrowSingleSigJson, WasabiJson,
+    },
+    key_expression::{HardenedMarker, KeyExpression, PathFormat},
+    script_type::{ScriptType, Warning},
+    wallet_policy::{self, WalletPolicy},
+    xpub::{self, ByteOrder, OriginalFormat},
 };
 
+/// Largest index a wildcard can be derived at, indexes from 2^31 are hardened and can't be
+/// derived from an xpub
+pub const MAX_DERIVATION_INDEX: u32 = (1 << 31) - 1;
+
+/// Written as the `ColdCardFirmwareVersion` of Wasabi exports, Wasabi requires the field but
+/// doesn't use it
+const WASABI_FIRMWARE_VERSION: &str = "5.4.0";
+
+/// The wallet file version Electrum 3.2 and later write
+const ELECTRUM_SEED_VERSION: u32 = 17;
+
+/// Largest input accepted when parsing descriptors, real exports are a few kilobytes at most
+pub const MAX_INPUT_BYTES: usize = 64 * 1024;
+
+/// Most lines accepted when parsing descriptors
+pub const MAX_INPUT_LINES: usize = 64;
+
+thread_local! {
+    /// Context for parsing descriptors, created once per thread instead of for every descriptor
+    static SECP: secp256k1::Secp256k1<secp256k1::SignOnly> = secp256k1::Secp256k1::signing_only();
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("Invalid descriptor: {0:?}")]
@@ -39,7 +81,7 @@ pub enum Error {
     #[error("Missing fingerprint (xfp)")]
     MissingFingerprint,
 
-    #[error("Unable to parse xpub: {0:?}")]
+    #[error("Unable to parse xpub: {0}")]
     InvalidXpub(#[from] xpub::Error),
 
     #[error("Unable to parse xpub: {0}")]
@@ -48,8 +90,171 @@ pub enum Error {
     #[error("Unable to get xpub from descriptor")]
     NoXpubInDescriptor,
 
+    #[error("Expected one key in the descriptor, found {0}")]
+    MultipleKeys(usize),
+
     #[error("Single pubkey is not supported, must be an extended key")]
     SinglePubkeyNotSupported,
+
+    #[error("Invalid descriptor checksum, expected {expected} found {found}")]
+    InvalidChecksum { expected: String, found: String },
+
+    #[error("Descriptor has no #checksum")]
+    MissingChecksum,
+
+    #[error("Unable to derive descriptor: {0}")]
+    UnableToDerive(#[from] ConversionError),
+
+    #[error("Unable to get address from descriptor: {0}")]
+    UnableToGetAddress(miniscript::Error),
+
+    #[error("Invalid message signature: {0}")]
+    InvalidSignature(MessageSignatureError),
+
+    #[error("Input too large, must be under {MAX_INPUT_BYTES} bytes and {MAX_INPUT_LINES} lines")]
+    InputTooLarge,
+
+    /// Account keys are hardened children of the master key, they can't be derived from the
+    /// master xpub without the private key
+    #[error("Expected an account xpub, got a master xpub, the account keys are hardened and can't be derived without the private key, export the account xpub instead")]
+    MasterXpub,
+
+    #[error("Receive and change keychains must be different, both are {0}")]
+    SameKeychains(u32),
+
+    #[error("Receive and change descriptors don't share their keys, can't write one multipath descriptor")]
+    KeychainsNotMultipath,
+
+    #[error("Keychains are <{receive};{change}>, not the standard <0;1>")]
+    NonContiguousMultipath { receive: String, change: String },
+
+    #[error("No active receive and change descriptor pair found")]
+    NoActiveDescriptors,
+
+    #[error("Derivation index {0} is out of range")]
+    IndexOutOfRange(u32),
+
+    #[error("Wallet is encrypted, remove the password in Electrum and export it again")]
+    EncryptedWalletUnsupported,
+
+    #[error("Stated master fingerprint {stated} doesn't match the xpub's {computed}")]
+    FingerprintXpubMismatch {
+        stated: Fingerprint,
+        computed: Fingerprint,
+    },
+
+    #[error(
+        "Can't verify the master fingerprint against an xpub at depth {0}, it only records its parent's fingerprint"
+    )]
+    FingerprintUnverifiable(u8),
+
+    #[error("Key already has the master fingerprint {existing}, not {new}")]
+    FingerprintConflict {
+        existing: Fingerprint,
+        new: Fingerprint,
+    },
+
+    #[error("Only bitcoin accounts are supported, got {0}")]
+    UnsupportedCurrency(String),
+
+    #[error("Can't set one master fingerprint for every cosigner of a multisig")]
+    MultisigFingerprint,
+
+    #[error("{0}")]
+    ScriptTypeMismatch(#[from] Warning),
+
+    #[error("Fixed descriptors must be pkh or wpkh with a single compressed pubkey")]
+    NotFixedSinglePubkey,
+
+    #[error("A fixed descriptor only has an address at index 0, not {0}")]
+    FixedIndex(u32),
+
+    #[error("Receive descriptor keys must end in /0/*, got {0}")]
+    NotReceiveKeychain(String),
+
+    #[error("Keys in the descriptor are for different networks: {networks:?}")]
+    MixedNetworks { networks: Vec<Network> },
+
+    #[error("Invalid address: {0}")]
+    InvalidAddress(String),
+
+    #[error("Expected the first address to be {expected}, but the descriptor derives {got}")]
+    FirstAddressMismatch { expected: String, got: String },
+
+    #[error("Wasabi only imports single sig P2WPKH wallets with /0/* and /1/* keychains")]
+    NotWasabiCompatible,
+
+    #[error("Expected a single sig wallet with one keystore, found {0}")]
+    NotSingleSig(usize),
+
+    #[error("Expected a ranged key ending in /*, got {0}")]
+    NotRangedKey(String),
+
+    #[error("Descriptor has a private key, only public keys can be imported, export the watch-only descriptor instead")]
+    PrivateKeyNotAllowed,
+
+    #[error("MuSig2 `musig()` keys aren't supported yet, the keys inside can't be read")]
+    MuSigNotSupported,
+
+    #[error("{0} wallets can't be exported in this format")]
+    UnsupportedScriptType(ScriptType),
+
+    #[error("Signed messages can only be verified for P2PKH, P2SH-P2WPKH and P2WPKH wallets")]
+    MessageVerificationUnsupported,
+
+    #[error("Descriptor for {network} has a key for {key_network:?}")]
+    NetworkKeyMismatch {
+        network: Network,
+        key_network: NetworkKind,
+    },
+}
+
+/// Which of the two descriptors in [`Descriptors`] to use, receive (external) or change (internal)
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    derive_more::Display,
+)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum Keychain {
+    External,
+    Internal,
+}
+
+impl Keychain {
+    /// Both keychains, receive first, for looping over the two descriptors
+    pub const ALL: [Keychain; 2] = [Keychain::External, Keychain::Internal];
+}
+
+/// How closely a single sig wallet's origin path follows the BIP for its script type, see
+/// [`Descriptors::standard_conformance`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum Conformance {
+    /// Exactly `purpose'/coin_type'/0'`, `84'/0'/0'` for a mainnet P2WPKH wallet
+    Standard,
+
+    /// The right purpose and layout, but a coin type or account that isn't the standard one,
+    /// each field is set to the value found when it deviates
+    NonStandard {
+        coin_type: Option<u32>,
+        account: Option<u32>,
+    },
+
+    /// The origin path doesn't follow the BIP layout, the purpose doesn't match the script type
+    /// or the path isn't three hardened steps
+    Custom,
+
+    /// Multisig and other scripts, or keys without an origin, there is no standard to check
+    Unknown,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -57,7 +262,7 @@ struct JsonDescriptor {
     descriptor: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Object))]
 pub struct Descriptors {
     #[serde(
@@ -70,13 +275,89 @@ pub struct Descriptors {
         deserialize_with = "deserialize_descriptor"
     )]
     pub internal: Descriptor<DescriptorPublicKey>,
+
+    /// Exactly what was parsed, for display only, not part of equality
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    original_external: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    original_internal: Option<String>,
+
+    /// Unix time the wallet was created at, when the export includes it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    birthday: Option<u64>,
+
+    /// Prefix the account key was imported with, `zpub` for most Electrum wallets, `None` when
+    /// it came from a descriptor
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    original_format: Option<OriginalFormat>,
+
+    /// Master fingerprint exactly as the export wrote it, `817E7BE0`, when it wasn't part of a
+    /// descriptor, for display only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source_fingerprint: Option<String>,
+}
+
+// the original strings are only kept for display, two imports of the same wallet are equal
+// regardless of how the descriptors were written
+impl PartialEq for Descriptors {
+    fn eq(&self, other: &Self) -> bool {
+        (&self.external, &self.internal) == (&other.external, &other.internal)
+    }
+}
+
+impl Eq for Descriptors {}
+
+impl std::hash::Hash for Descriptors {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.external.hash(state);
+        self.internal.hash(state);
+    }
+}
+
+impl PartialOrd for Descriptors {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Descriptors {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (&self.external, &self.internal).cmp(&(&other.external, &other.internal))
+    }
 }
 
 impl Descriptors {
+    /// Parse a line we built ourselves, so there is no original string to keep
+    pub(crate) fn try_from_generated_line(line: &str) -> Result<Self, Error> {
+        let mut desc = Self::try_from_line(line)?;
+        desc.original_external = None;
+        desc.original_internal = None;
+
+        Ok(desc)
+    }
+
+    /// The descriptor exactly as it was given, before it was parsed and re-serialized
+    ///
+    /// A multipath line is the original for both keychains. `None` when the descriptors were
+    /// built from other data, like an xpub and derivation path.
+    pub fn original_string(&self, keychain: Keychain) -> Option<&str> {
+        match keychain {
+            Keychain::External => self.original_external.as_deref(),
+            Keychain::Internal => self.original_internal.as_deref(),
+        }
+    }
+
     pub fn try_from_line(line: &str) -> Result<Self, Error> {
-        let secp = &secp256k1::Secp256k1::signing_only();
-        let (descriptor, _keymap) =
-            Descriptor::<DescriptorPublicKey>::parse_descriptor(secp, line)?;
+        SECP.with(|secp| Self::try_from_line_with_secp(line, secp))
+    }
+
+    /// Same as [`Descriptors::try_from_line`] but with a caller provided secp context, so batch
+    /// importers can create one context and reuse it for every descriptor
+    pub fn try_from_line_with_secp<C: secp256k1::Signing>(
+        line: &str,
+        secp: &secp256k1::Secp256k1<C>,
+    ) -> Result<Self, Error> {
+        let descriptor = parse_descriptor(secp, line)?;
 
         if !descriptor.is_multipath() {
             return Err(Error::MissingKeys);
@@ -93,9 +374,124 @@ impl Descriptors {
         Ok(Self {
             external: multi[0].clone(),
             internal: multi[1].clone(),
+            original_external: Some(line.to_string()),
+            original_internal: Some(line.to_string()),
+            birthday: None,
+            original_format: None,
+            source_fingerprint: None,
+        })
+    }
+
+    /// Build both descriptors from only the receive descriptor, every key must end in `/0/*` and
+    /// the change descriptor uses `/1/*` on the same keys
+    ///
+    /// For tools that only export `wpkh([817e7be0/84h/0h/0h]xpub.../0/*)`
+    pub fn try_from_external_only(line: &str) -> Result<Self, Error> {
+        let external = SECP.with(|secp| parse_descriptor(secp, line))?;
+
+        let internal = external
+            .translate_pk(&mut ChangeKeychainTranslator)
+            .map_err(translate_error)?;
+
+        Ok(Self {
+            external,
+            internal,
+            original_external: Some(line.to_string()),
+            original_internal: None,
+            birthday: None,
+            original_format: None,
+            source_fingerprint: None,
+        })
+    }
+
+    /// Descriptors for a single ranged key, without going through a descriptor string
+    ///
+    /// A multipath key, `xpub.../<0;1>/*`, is split into the two keychains. A key ending in
+    /// `/0/*` is the receive keychain and gets `/1/*` for change, the same as
+    /// [`Descriptors::try_from_external_only`].
+    pub fn from_key(key: DescriptorPublicKey, script_type: ScriptType) -> Result<Self, Error> {
+        if !key.has_wildcard() {
+            return Err(Error::NotRangedKey(key.to_string()));
+        }
+
+        let (external, internal) = match key.is_multipath() {
+            true => match <[_; 2]>::try_from(key.into_single_keys()) {
+                Ok([external, internal]) => (external, internal),
+                Err(keys) => return Err(Error::TooManyKeys(keys.len())),
+            },
+            false => {
+                let internal = ChangeKeychainTranslator.pk(&key)?;
+                (key, internal)
+            }
+        };
+
+        Ok(Self {
+            external: script_descriptor(external, script_type)?,
+            internal: script_descriptor(internal, script_type)?,
+            original_external: None,
+            original_internal: None,
+            birthday: None,
+            original_format: None,
+            source_fingerprint: None,
         })
     }
 
+    /// Same as [`Descriptors::try_from_single_sig`], but errors if the stated fingerprint
+    /// doesn't match the xpub, see [`verify_master_fingerprint`], or if the `first` address
+    /// isn't the first receive address, see [`Descriptors::verify_first_address`]
+    ///
+    /// An account xpub can't be checked against the fingerprint, so with one it is an
+    /// [`Error::FingerprintUnverifiable`].
+    pub fn try_from_single_sig_verified(
+        single_sig: SingleSig,
+        fingerprint: Option<&str>,
+    ) -> Result<Self, Error> {
+        if let (Some(xpub), Some(fingerprint)) = (&single_sig.xpub, fingerprint) {
+            let fingerprint = xpub::parse_xfp(fingerprint, ByteOrder::BigEndian)?;
+            verify_master_fingerprint(fingerprint, &xpub::Xpub::try_from(xpub.as_str())?)?;
+        }
+
+        Self::try_from_single_sig_first_verified(single_sig, fingerprint)
+    }
+
+    /// Only the `first` address check of [`Descriptors::try_from_single_sig_verified`], for
+    /// exports where the fingerprint was already checked against the master xpub
+    pub(crate) fn try_from_single_sig_first_verified(
+        single_sig: SingleSig,
+        fingerprint: Option<&str>,
+    ) -> Result<Self, Error> {
+        let first = single_sig.first.clone();
+        let desc = Self::try_from_single_sig(single_sig, fingerprint)?;
+
+        if let Some(first) = first {
+            desc.verify_first_address(&first)?;
+        }
+
+        Ok(desc)
+    }
+
+    /// Same as [`Descriptors::try_from_electrum`], but errors if the keystore's `ckcc_xfp`
+    /// doesn't match its `ckcc_xpub` (the Coldcard master xpub), see
+    /// [`verify_master_fingerprint`]
+    ///
+    /// Without a `ckcc_xpub` the keystore xpub is used, which for an account xpub is an
+    /// [`Error::FingerprintUnverifiable`].
+    pub fn try_from_electrum_verified(
+        json: ElectrumJson,
+        strict: bool,
+    ) -> Result<(Self, Option<Warning>), Error> {
+        let keystore = &json.keystore;
+
+        if let Some(xfp) = keystore.ckcc_xfp {
+            let stated = xpub::fingerprint_from_u32(xfp, ByteOrder::LittleEndian);
+
+            let xpub = keystore.ckcc_xpub.as_ref().unwrap_or(&keystore.xpub);
+            verify_master_fingerprint(stated, &xpub::Xpub::try_from(xpub.as_str())?)?;
+        }
+
+        Self::try_from_electrum(json, strict)
+    }
+
     pub fn try_from_single_sig(
         single_sig: SingleSig,
         fingerprint: Option<&str>,
@@ -108,439 +504,3061 @@ impl Descriptors {
         let script_type = single_sig.name.ok_or(Error::MissingScriptType)?;
         let xpub = single_sig.xpub.ok_or(Error::MissingXpub)?;
 
-        let fingerprint = fingerprint
-            .ok_or(Error::MissingFingerprint)?
-            .to_ascii_lowercase();
+        let source_fingerprint = fingerprint.ok_or(Error::MissingFingerprint)?;
+        let fingerprint = xpub::parse_xfp(source_fingerprint, ByteOrder::BigEndian)?;
+
+        let derivation_path = single_sig.deriv.ok_or(Error::MissingDerivationPath)?;
+        let derivation_path = strip_master_prefix(&derivation_path);
 
-        let derivation_path = single_sig
-            .deriv
-            .ok_or(Error::MissingDerivationPath)?
-            .replace("m/", "");
+        let script = multipath_key(fingerprint, derivation_path, &xpub);
+        let desc = script_type.wrap_with(&script);
 
-        let script = format!("[{fingerprint}/{derivation_path}]{xpub}/<0;1>/*");
-        let desc = wrap_in_script_type(script_type, &script);
+        let mut desc = Descriptors::try_from_generated_line(&desc)?;
+        desc.source_fingerprint = Some(source_fingerprint.trim().to_string());
 
-        let desc = Descriptors::try_from_line(&desc)?;
         Ok(desc)
     }
 
-    pub fn fingerprint(&self) -> Option<Fingerprint> {
-        let desc = &self.external;
+    /// The receive (external) or change (internal) descriptor
+    pub fn descriptor(&self, keychain: Keychain) -> &Descriptor<DescriptorPublicKey> {
+        match keychain {
+            Keychain::External => &self.external,
+            Keychain::Internal => &self.internal,
+        }
+    }
 
-        let inner = match desc {
-            Descriptor::Pkh(pkh) => Some(pkh.as_inner()),
-            Descriptor::Wpkh(wpkh) => Some(wpkh.as_inner()),
-            Descriptor::Wsh(_) => None,
-            Descriptor::Sh(_) => None,
-            Descriptor::Tr(_) => None,
-            Descriptor::Bare(_) => None,
-        }?;
+    /// Both descriptors as `(external, internal)`
+    pub fn as_tuple(
+        &self,
+    ) -> (
+        &Descriptor<DescriptorPublicKey>,
+        &Descriptor<DescriptorPublicKey>,
+    ) {
+        (&self.external, &self.internal)
+    }
 
-        Some(inner.master_fingerprint())
+    /// Derive the address at `index` of the receive (external) or change (internal) descriptor
+    ///
+    /// Use [`crate::deriver::DescriptorDeriver`] when deriving many addresses
+    pub fn address_at(
+        &self,
+        keychain: Keychain,
+        index: u32,
+        network: Network,
+    ) -> Result<Address, Error> {
+        check_index(index, MAX_DERIVATION_INDEX)?;
+
+        self.descriptor(keychain)
+            .at_derivation_index(index)?
+            .address(network)
+            .map_err(Error::UnableToGetAddress)
     }
 
-    pub fn xpub(&self) -> Result<bitcoin::bip32::Xpub, Error> {
-        let desc = &self.external;
+    /// Number of keys in the wallet, 1 for single sig and the number of cosigners for multisig
+    pub fn cosigner_count(&self) -> usize {
+        wallet_keys(&self.external).len()
+    }
 
-        let inner = match desc {
-            Descriptor::Pkh(pkh) => pkh.as_inner(),
-            Descriptor::Wpkh(wpkh) => wpkh.as_inner(),
-            Descriptor::Wsh(_) => return Err(Error::NoXpubInDescriptor),
-            Descriptor::Sh(_) => return Err(Error::NoXpubInDescriptor),
-            Descriptor::Tr(_) => return Err(Error::NoXpubInDescriptor),
-            Descriptor::Bare(_) => return Err(Error::NoXpubInDescriptor),
+    /// Both keychains as one multipath descriptor with its checksum, as miniscript writes it
+    ///
+    /// Two imports of the same wallet give the same string however the descriptors were written,
+    /// so it can be used as a storage key. If the keychains don't share their keys (only possible
+    /// when the fields were set by hand) the two descriptors are returned on separate lines.
+    pub fn canonical_string(&self) -> String {
+        self.to_multipath_line().unwrap_or_else(|_| {
+            let (external, internal) = self.to_separate_lines();
+            format!("{external}\n{internal}")
+        })
+    }
+
+    /// Both keychains as one BIP389 `<0;1>` multipath descriptor with its checksum, the shape
+    /// Sparrow and BDK import
+    ///
+    /// Errors if the keychains don't share their keys, which is only possible when the fields were
+    /// set by hand.
+    pub fn to_multipath_line(&self) -> Result<String, Error> {
+        Ok(self.multipath_descriptor()?.to_string())
+    }
+
+    /// Same as [`Descriptors::to_multipath_line`], but only for the standard `<0;1>` keychains
+    ///
+    /// Errors with [`Error::NonContiguousMultipath`] for wallets with other keychains, like
+    /// `<2;3>` from [`Descriptors::try_from_child_xpub_with_keychains`], which many wallets can't
+    /// import.
+    pub fn to_multipath_string(&self) -> Result<String, Error> {
+        let descriptor = self.multipath_descriptor()?;
+
+        for key in descriptor_keys(&descriptor) {
+            let DescriptorPublicKey::MultiXPub(xkey) = key else {
+                continue;
+            };
+
+            let [receive, change] = xkey.derivation_paths.paths().as_slice() else {
+                return Err(Error::KeychainsNotMultipath);
+            };
+
+            let (Some((receive, receive_rest)), Some((change, change_rest))) =
+                (receive.as_ref().split_last(), change.as_ref().split_last())
+            else {
+                return Err(Error::KeychainsNotMultipath);
+            };
+
+            if receive_rest != change_rest {
+                return Err(Error::KeychainsNotMultipath);
+            }
+
+            if (*receive, *change)
+                != (
+                    ChildNumber::Normal { index: 0 },
+                    ChildNumber::Normal { index: 1 },
+                )
+            {
+                return Err(Error::NonContiguousMultipath {
+                    receive: receive.to_string(),
+                    change: change.to_string(),
+                });
+            }
+        }
+
+        Ok(descriptor.to_string())
+    }
+
+    /// Same as [`Descriptors::to_multipath_line`], but errors if any extended key is for another
+    /// network than `network`, so the `xpub` or `tpub` prefixes always match it
+    pub fn network_prefixed_descriptor(&self, network: Network) -> Result<String, Error> {
+        self.check_network(network)?;
+        self.to_multipath_line()
+    }
+
+    /// The wallet as a watch only Electrum wallet file, the inverse of
+    /// [`Descriptors::try_from_electrum`]
+    ///
+    /// The key is written with the SLIP-132 prefix for its script type, Electrum reads the
+    /// script type from it. Electrum has no single sig taproot wallets.
+    pub fn to_electrum_json(&self, network: Network) -> Result<ElectrumJson, Error> {
+        self.check_network(network)?;
+
+        let script_type = self
+            .script_type()
+            .ok_or(Error::NotSingleSig(self.cosigner_count()))?;
+
+        if script_type == ScriptType::P2tr {
+            return Err(Error::UnsupportedScriptType(script_type));
+        }
+
+        let (fingerprint, path) = self.origin().ok_or(Error::MissingFingerprint)?;
+
+        let keystore = Keystore {
+            keystore_type: Some("bip32".to_string()),
+            derivation: PathFormat::MASTER_H.format_path(&path),
+            xpub: self.slip132_xpub()?,
+            ckcc_xfp: Some(u32::from_le_bytes(fingerprint.to_bytes())),
+            ckcc_xpub: None,
         };
 
-        let xpub: bitcoin::bip32::Xpub = match inner {
-            DescriptorPublicKey::XPub(inner) => inner.xkey,
-            DescriptorPublicKey::MultiXPub(inner) => inner.xkey,
-            DescriptorPublicKey::Single(_) => return Err(Error::SinglePubkeyNotSupported),
+        Ok(ElectrumJson {
+            seed_version: ELECTRUM_SEED_VERSION,
+            use_encryption: false,
+            wallet_type: "standard".to_string(),
+            keystore,
+        })
+    }
+
+    /// Errors if any extended key is for another network than `network`
+    fn check_network(&self, network: Network) -> Result<(), Error> {
+        let expected = NetworkKind::from(network);
+
+        match self
+            .extended_keys()
+            .iter()
+            .find(|key| key.network != expected)
+        {
+            Some(key) => Err(Error::NetworkKeyMismatch {
+                network,
+                key_network: key.network,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn multipath_descriptor(&self) -> Result<Descriptor<DescriptorPublicKey>, Error> {
+        let mut translator = MultipathTranslator {
+            internal: descriptor_keys(&self.internal),
         };
 
-        Ok(xpub)
+        self.external
+            .translate_pk(&mut translator)
+            .map_err(|_| Error::KeychainsNotMultipath)
     }
-}
 
-#[cfg(feature = "uniffi")]
-mod ffi {
-    use super::Descriptors;
+    /// The wallet as a BIP388 wallet policy, `wpkh(@0/**)` and its key, the form Ledger devices
+    /// register wallets in
+    pub fn to_wallet_policy(&self) -> Result<WalletPolicy, wallet_policy::Error> {
+        WalletPolicy::try_from_descriptors(self)
+    }
 
-    impl Descriptors {
-        pub fn external(&self) -> String {
-            self.external.to_string()
+    /// The wallet as a Wasabi import file, the inverse of the [`WasabiJson`] import
+    ///
+    /// Only the first account, `84'/0'/0'`, was supported by older Wasabi versions. Other
+    /// accounts are written with an `AccountKeyPath`.
+    pub fn to_wasabi_json(&self) -> Result<WasabiJson, Error> {
+        if self.script_type() != Some(ScriptType::P2wpkh) {
+            return Err(Error::NotWasabiCompatible);
         }
 
-        pub fn internal(&self) -> String {
-            self.internal.to_string()
+        let (fingerprint, path) = self.origin().ok_or(Error::NotWasabiCompatible)?;
+        let account_key_path = match PathFormat::MASTER_APOSTROPHE.format_path(&path) {
+            path if path == "m/84'/0'/0'" => None,
+            path => Some(path),
+        };
+
+        let json = WasabiJson {
+            cold_card_firmware_version: WASABI_FIRMWARE_VERSION.to_string(),
+            master_fingerprint: fingerprint.to_string().to_uppercase(),
+            ext_pub_key: self.xpub()?.to_string(),
+            account_key_path,
+        };
+
+        // anything Wasabi can't express, like a change keychain other than /1/*, won't round trip
+        if Descriptors::try_from(json.clone()).ok().as_ref() != Some(self) {
+            return Err(Error::NotWasabiCompatible);
         }
-    }
-}
 
-impl TryFrom<WasabiJson> for Descriptors {
-    type Error = Error;
+        Ok(json)
+    }
 
-    fn try_from(json: WasabiJson) -> Result<Self, Self::Error> {
-        let fingerprint = json.master_fingerprint.to_ascii_lowercase();
-        let derivation_path = "84h/0h/0h";
-        let xpub = json.ext_pub_key;
+    /// The receive and change descriptors as separate `/0/*` and `/1/*` lines with their
+    /// checksums, the shape older versions of Bitcoin Core import
+    ///
+    /// miniscript computes the checksums again, so use this (or
+    /// [`Descriptors::to_multipath_line`]) when writing descriptors out after a change like
+    /// [`Descriptors::with_master_fingerprint`]. [`Descriptors::original_string`] is only for
+    /// display, it keeps whatever checksum the input had, or none.
+    #[doc(alias = "with_fresh_checksums", alias = "rederive_checksum")]
+    pub fn to_separate_lines(&self) -> (String, String) {
+        (self.external_with_checksum(), self.internal_with_checksum())
+    }
 
-        let script = format!("[{fingerprint}/{derivation_path}]{xpub}/<0;1>/*");
-        let desc = wrap_in_script_type(Name::P2wpkh, &script);
+    /// Same as [`Descriptors::to_separate_lines`], with every hardened step written with `marker`
+    /// and the checksums computed for the rewritten lines
+    pub fn to_separate_lines_with_marker(&self, marker: HardenedMarker) -> (String, String) {
+        (
+            with_marker(&self.external, marker),
+            with_marker(&self.internal, marker),
+        )
+    }
 
-        let desc = Descriptors::try_from_line(&desc)?;
-        Ok(desc)
+    /// Same as [`Descriptors::to_multipath_line`], with every hardened step written with `marker`
+    pub fn to_multipath_line_with_marker(&self, marker: HardenedMarker) -> Result<String, Error> {
+        Ok(with_marker(&self.multipath_descriptor()?, marker))
     }
-}
 
-impl TryFrom<ElectrumJson> for Descriptors {
-    type Error = Error;
+    /// The receive descriptor with its BIP380 `#checksum`, computed by miniscript
+    ///
+    /// Descriptors built from other formats, like [`Descriptors::try_from_single_sig`], never
+    /// had a checksum, this is the one to write out to wallets that require it.
+    pub fn external_with_checksum(&self) -> String {
+        self.external.to_string()
+    }
 
-    fn try_from(json: ElectrumJson) -> Result<Self, Self::Error> {
-        let keystore = &json.keystore;
+    /// The change descriptor with its BIP380 `#checksum`, see
+    /// [`Descriptors::external_with_checksum`]
+    pub fn internal_with_checksum(&self) -> String {
+        self.internal.to_string()
+    }
 
-        let mut script_type = None;
-        if keystore.derivation.starts_with("m/84") {
-            script_type = Some(Name::P2wpkh);
+    /// Replace the placeholder `00000000` master fingerprint, like the one
+    /// [`Descriptors::try_from_child_xpub`] uses, with the real one from the device
+    ///
+    /// A key that already has a different fingerprint is an [`Error::FingerprintConflict`].
+    /// Single sig only, each cosigner of a multisig has its own fingerprint.
+    pub fn with_master_fingerprint(&self, fingerprint: Fingerprint) -> Result<Self, Error> {
+        if self.cosigner_count() > 1 {
+            return Err(Error::MultisigFingerprint);
         }
 
-        if keystore.derivation.starts_with("m/49") {
-            script_type = Some(Name::P2shP2wpkh);
-        }
+        let mut translator = FingerprintTranslator { fingerprint };
+        let external = self
+            .external
+            .translate_pk(&mut translator)
+            .map_err(translate_error)?;
+
+        let internal = self
+            .internal
+            .translate_pk(&mut translator)
+            .map_err(translate_error)?;
+
+        Ok(Self {
+            external,
+            internal,
+            original_external: None,
+            original_internal: None,
+            birthday: self.birthday,
+            original_format: self.original_format,
+            source_fingerprint: None,
+        })
+    }
+
+    /// Descriptors for an account xpub exported without its key origin
+    ///
+    /// The origin uses the standard BIP44/49/84 account path for `script_type` and the
+    /// placeholder master fingerprint `00000000`, since neither can be recovered from the xpub.
+    ///
+    /// A master xpub is rejected with [`Error::MasterXpub`], the account paths are all hardened
+    /// so the account keys can't be derived from it.
+    pub fn try_from_child_xpub(xpub: &str, script_type: ScriptType) -> Result<Self, Error> {
+        Self::try_from_child_xpub_with_keychains(xpub, script_type, 0, 1)
+    }
+
+    /// Descriptors for an account `xpub`, `ypub` or `zpub` with a known script type
+    ///
+    /// Same as [`Descriptors::try_from_child_xpub`], but a `ypub` or `zpub` must match the script
+    /// type, so a `zpub` given as P2PKH is an error instead of a wallet with the wrong addresses.
+    pub fn try_from_xpub_str(xpub_str: &str, script_type: ScriptType) -> Result<Self, Error> {
+        let xpub = xpub::Xpub::try_from(xpub_str.trim())?;
 
-        if keystore.derivation.starts_with("m/44") {
-            script_type = Some(Name::P2pkh);
+        if let Some(warning) = script_type.check_slip132(&xpub.original_format()) {
+            return Err(warning.into());
         }
 
-        if script_type.is_none() {
-            return Err(Error::MissingScriptType);
+        Self::try_from_child_xpub(xpub_str, script_type)
+    }
+
+    /// Import an Electrum single sig wallet, also returning a warning if the key prefix and the
+    /// derivation path are for different script types, like a `zpub` with an `m/44h` path
+    ///
+    /// The script type comes from the derivation path, the prefix is only used when the path
+    /// doesn't have a known purpose. With `strict` the mismatch is an
+    /// [`Error::ScriptTypeMismatch`] instead.
+    pub fn try_from_electrum(
+        json: ElectrumJson,
+        strict: bool,
+    ) -> Result<(Self, Option<Warning>), Error> {
+        if json.use_encryption {
+            return Err(Error::EncryptedWalletUnsupported);
         }
 
-        let script_type = script_type.expect("checked above");
+        let keystore = &json.keystore;
+
         if keystore.xpub.len() < 4 {
             return Err(xpub::Error::TooShort(keystore.xpub.len()).into());
         }
 
         let xpub = xpub::Xpub::try_from(keystore.xpub.as_str())?;
 
-        let fingerprint = match (&keystore.ckcc_xfp, &keystore.ckcc_xpub) {
-            (Some(fingerprint), _) => {
-                let xfp = fingerprint.swap_bytes();
-                format!("{:08X}", xfp)
-            }
-            (None, Some(ck_xpub)) => xpub::xpub_to_fingerprint(ck_xpub)?.to_string(),
-            (None, None) => xpub.fingerprint()?.to_string(),
-        };
+        let from_path = DerivationPath::from_str(&keystore.derivation)
+            .ok()
+            .and_then(|path| ScriptType::try_from_derivation_path(&path));
+
+        let warning =
+            from_path.and_then(|from_path| from_path.check_slip132(&xpub.original_format()));
+        if let (Some(warning), true) = (warning, strict) {
+            return Err(warning.into());
+        }
+
+        let script_type = from_path
+            .or_else(|| ScriptType::from_slip132(&xpub.original_format()))
+            .ok_or(Error::MissingScriptType)?;
+
+        let fingerprint = electrum_fingerprint(keystore, &xpub)?;
+
+        let derivation_path = strip_master_prefix(&keystore.derivation);
+        let script = multipath_key(fingerprint, derivation_path, &xpub);
+        let desc = script_type.wrap_with(&script);
+
+        let mut desc = Descriptors::try_from_generated_line(&desc)?;
+        desc.original_format = Some(xpub.original_format());
+
+        Ok((desc, warning))
+    }
 
-        let derivation_path = keystore.derivation.replace("m/", "");
-        let script = format!("[{fingerprint}/{derivation_path}]{xpub}/<0;1>/*");
-        let desc = wrap_in_script_type(script_type, &script);
+    /// Same as [`Descriptors::try_from_child_xpub`] but with custom keychain indexes, for wallets
+    /// that don't use `0` for receive and `1` for change, `/<receive;change>/*`
+    pub fn try_from_child_xpub_with_keychains(
+        xpub: &str,
+        script_type: ScriptType,
+        receive: u32,
+        change: u32,
+    ) -> Result<Self, Error> {
+        if receive == change {
+            return Err(Error::SameKeychains(receive));
+        }
+
+        let xpub = xpub::Xpub::try_from(xpub.trim())?;
+        if xpub.depth() == 0 {
+            return Err(Error::MasterXpub);
+        }
+
+        let derivation_path = script_type.account_derivation_path();
+        let script = keychains_key("00000000", derivation_path, &xpub, receive, change);
+        let desc = script_type.wrap_with(&script);
+
+        let mut desc = Descriptors::try_from_generated_line(&desc)?;
+        desc.original_format = Some(xpub.original_format());
 
-        let desc = Descriptors::try_from_line(&desc)?;
         Ok(desc)
     }
-}
 
-impl TryFrom<&str> for Descriptors {
-    type Error = Error;
+    /// Descriptors for a bare key expression, `[817e7be0/84h/0h/0h]xpub.../<0;1>/*`
+    ///
+    /// The script type comes from the purpose in the key origin, or else the SLIP-132 prefix of
+    /// the key. A key without an origin gets the placeholder origin used by
+    /// [`Descriptors::try_from_child_xpub`], and a key without a multipath step gets `<0;1>/*`.
+    pub fn try_from_key_expression(key: &KeyExpression) -> Result<Self, Error> {
+        let script_type = ScriptType::try_from_derivation_path(&key.origin)
+            .or_else(|| ScriptType::from_slip132(&key.xpub.original_format()))
+            .ok_or(Error::MissingScriptType)?;
+
+        let (receive, change) = match key.multipath {
+            Some(multipath) => multipath,
+            None if key.derivation.is_empty() => (0, 1),
+            None => return Err(Error::MissingKeys),
+        };
 
-    fn try_from(desc: &str) -> Result<Self, Self::Error> {
-        let lines = desc
-            .trim()
-            .split('\n')
-            .filter(|line| !line.is_empty())
-            .map(|line| line.trim())
-            .collect::<Vec<&str>>();
+        if receive == change {
+            return Err(Error::SameKeychains(receive));
+        }
 
-        if let Some(line) = lines.first() {
-            // json descriptor
-            if line.trim().starts_with('{') {
-                let json: JsonDescriptor =
-                    serde_json::from_str(desc).map_err(Error::InvalidJsonDescriptor)?;
-                return Self::try_from_line(&json.descriptor);
+        let mut script = match key.fingerprint {
+            Some(fingerprint) if key.origin.is_empty() => format!("[{fingerprint}]"),
+            Some(fingerprint) => {
+                let origin = HardenedMarker::H.format_path(&key.origin);
+                format!("[{fingerprint}/{origin}]")
             }
+            None => format!("[00000000/{}]", script_type.account_derivation_path()),
+        };
+
+        script.push_str(key.xpub.as_str());
+        for child in &key.derivation {
+            script.push_str(&format!("/{child}"));
         }
 
-        match lines.len() {
-            1 => Descriptors::try_from_line(lines[0]),
-            2 => {
-                let external = lines[0];
-                let internal = lines[1];
+        script.push_str(&format!("/<{receive};{change}>"));
+        if key.wildcard || key.multipath.is_none() {
+            script.push_str("/*");
+        }
 
-                let secp = &secp256k1::Secp256k1::signing_only();
-                let (internal_desc, _keymap) =
-                    Descriptor::<DescriptorPublicKey>::parse_descriptor(secp, internal)?;
+        let desc = script_type.wrap_with(&script);
 
-                let (external_desc, _keymap) =
-                    Descriptor::<DescriptorPublicKey>::parse_descriptor(secp, external)?;
+        let mut desc = Descriptors::try_from_generated_line(&desc)?;
+        desc.original_format = Some(key.xpub.original_format());
 
-                Ok(Descriptors {
-                    external: external_desc,
-                    internal: internal_desc,
-                })
-            }
-            0 => Err(Error::MissingDescriptor),
-            n => Err(Error::TooManyKeys(n)),
-        }
+        Ok(desc)
     }
-}
 
-fn wrap_in_script_type(script_type: Name, script: &str) -> String {
-    match script_type {
-        Name::P2pkh => format!("pkh({})", script),
-        Name::P2shP2wpkh => format!("sh(wpkh({}))", script),
-        Name::P2wpkh => format!("wpkh({})", script),
+    /// Whether the descriptors were synthesized from a bare xpub, with an assumed derivation path
+    /// and the placeholder `00000000` master fingerprint, see [`Descriptors::try_from_child_xpub`]
+    ///
+    /// Descriptors that were given with a `00000000` fingerprint are not synthesized.
+    pub fn is_synthesized(&self) -> bool {
+        self.original_external.is_none()
+            && self
+                .fingerprints()
+                .iter()
+                .all(|fingerprint| fingerprint.as_bytes() == &[0; 4])
     }
-}
 
-fn serialize_descriptor<S>(
-    descriptor: &Descriptor<DescriptorPublicKey>,
-    serializer: S,
-) -> Result<S::Ok, S::Error>
-where
-    S: serde::Serializer,
-{
-    let desc = descriptor.to_string();
-    serializer.serialize_str(&desc)
+    /// Whether both keychains end in a `/*` wildcard, so they can derive any number of addresses
+    ///
+    /// A descriptor without a wildcard, like `wpkh(xpub/<0;1>)`, has a single address per
+    /// keychain. Only index 0 is meaningful for it, every index derives that same address.
+    pub fn is_ranged(&self) -> bool {
+        self.external.has_wildcard() && self.internal.has_wildcard()
+    }
+
+    /// Master fingerprints of every key in the wallet, in the order they appear in the descriptor
+    ///
+    /// Unlike [`Descriptors::fingerprint`] this also works for multisig, returning one fingerprint
+    /// per cosigner
+    pub fn fingerprints(&self) -> Vec<Fingerprint> {
+        wallet_keys(&self.external)
+            .into_iter()
+            .map(DescriptorPublicKey::master_fingerprint)
+            .collect()
+    }
+
+    /// Unix time the wallet was created at, from exports that include it like Bitcoin Core's
+    /// `listdescriptors`, so a rescan can start there
+    pub fn birthday_timestamp(&self) -> Option<u64> {
+        self.birthday
+    }
+
+    /// The extended keys of every key in the wallet, in the order they appear in the descriptor
+    ///
+    /// Single pubkeys are left out, so the list can be shorter than [`Descriptors::fingerprints`]
+    pub fn extended_keys(&self) -> Vec<bitcoin::bip32::Xpub> {
+        descriptor_keys(&self.external)
+            .into_iter()
+            .filter_map(|key| match key {
+                DescriptorPublicKey::XPub(xkey) => Some(xkey.xkey),
+                DescriptorPublicKey::MultiXPub(xkey) => Some(xkey.xkey),
+                DescriptorPublicKey::Single(_) => None,
+            })
+            .collect()
+    }
+
+    /// Errors unless `address` is the first receive address, index 0 of the external keychain
+    ///
+    /// The address is derived for the network the given address is for, so a testnet address
+    /// checks testnet keys.
+    pub fn verify_first_address(&self, address: &str) -> Result<(), Error> {
+        let invalid = || Error::InvalidAddress(address.to_string());
+        let expected = Address::<NetworkUnchecked>::from_str(address).map_err(|_| invalid())?;
+
+        let network = [
+            Network::Bitcoin,
+            Network::Testnet,
+            Network::Signet,
+            Network::Regtest,
+        ]
+        .into_iter()
+        .find(|network| expected.is_valid_for_network(*network))
+        .ok_or_else(invalid)?;
+
+        let got = self.address_at(Keychain::External, 0, network)?;
+        if got.as_unchecked() != &expected {
+            return Err(Error::FirstAddressMismatch {
+                expected: address.to_string(),
+                got: got.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// The network every extended key in the wallet is for, errors if mainnet and testnet keys
+    /// are mixed
+    ///
+    /// Extended keys only tell mainnet and testnet apart, so any test network is returned as
+    /// [`Network::Testnet`]. Single pubkeys, like the unspendable internal key of a taproot
+    /// multisig, have no network and are skipped.
+    pub fn validate_uniform_network(&self) -> Result<Network, Error> {
+        let mut networks = Vec::new();
+        for xpub in self.extended_keys() {
+            let network = match xpub.network {
+                NetworkKind::Main => Network::Bitcoin,
+                NetworkKind::Test => Network::Testnet,
+            };
+
+            if !networks.contains(&network) {
+                networks.push(network);
+            }
+        }
+
+        match networks[..] {
+            [network] => Ok(network),
+            [] => Err(Error::NoXpubInDescriptor),
+            _ => Err(Error::MixedNetworks { networks }),
+        }
+    }
+
+    /// The single sig script type, `None` for multisig and other scripts
+    pub fn script_type(&self) -> Option<ScriptType> {
+        match &self.external {
+            Descriptor::Pkh(_) => Some(ScriptType::P2pkh),
+            Descriptor::Wpkh(_) => Some(ScriptType::P2wpkh),
+            Descriptor::Sh(sh) => match sh.as_inner() {
+                ShInner::Wpkh(_) => Some(ScriptType::P2shP2wpkh),
+                _ => None,
+            },
+            Descriptor::Tr(tr) if tr.tap_tree().is_none() => Some(ScriptType::P2tr),
+            _ => None,
+        }
+    }
+
+    /// Master fingerprint and derivation path from the key origin, single sig only
+    pub fn origin(&self) -> Option<(Fingerprint, DerivationPath)> {
+        let [key] = wallet_keys(&self.external)[..] else {
+            return None;
+        };
+
+        match key {
+            DescriptorPublicKey::XPub(xkey) => xkey.origin.clone(),
+            DescriptorPublicKey::MultiXPub(xkey) => xkey.origin.clone(),
+            DescriptorPublicKey::Single(single) => single.origin.clone(),
+        }
+    }
+
+    /// The derivation path in the key origins, `m/84h/0h/0h` for a P2WPKH wallet
+    ///
+    /// Unlike [`Descriptors::origin`] this also works for multisig, where it is the path every
+    /// cosigner shares, usually the BIP48 one like `m/48h/0h/0h/2h`. `None` if the cosigners use
+    /// different paths or an extended key has no origin.
+    pub fn origin_derivation_path(&self) -> Option<DerivationPath> {
+        let mut paths = descriptor_keys(&self.external)
+            .into_iter()
+            .filter_map(|key| match key {
+                DescriptorPublicKey::XPub(xkey) => Some(xkey.origin.as_ref()),
+                DescriptorPublicKey::MultiXPub(xkey) => Some(xkey.origin.as_ref()),
+                // like the unspendable internal key of a taproot multisig
+                DescriptorPublicKey::Single(single) => single.origin.as_ref().map(Some),
+            })
+            .map(|origin| origin.map(|(_, path)| path));
+
+        let first = paths.next()??;
+        paths.all(|path| path == Some(first)).then(|| first.clone())
+    }
+
+    /// The master fingerprint exactly as the source wrote it, `817E7BE0` from a Coldcard JSON
+    /// export or `817e7be0` from a descriptor, single sig only like [`Descriptors::origin`]
+    ///
+    /// Falls back to the canonical lowercase form when the source had no fingerprint string, like
+    /// Electrum's integer `ckcc_xfp`, or when the source no longer matches the key origin.
+    pub fn source_fingerprint_string(&self) -> Option<String> {
+        let (fingerprint, _) = self.origin()?;
+
+        // the first key origin of the descriptor as given, `[817E7BE0/84h/0h/0h]`
+        let from_line = || {
+            let (_, origin) = self.original_external.as_deref()?.split_once('[')?;
+            origin.split(['/', ']']).next()
+        };
+
+        let source = self.source_fingerprint.as_deref().or_else(from_line);
+        match source {
+            Some(source)
+                if xpub::parse_xfp(source, ByteOrder::BigEndian).ok() == Some(fingerprint) =>
+            {
+                Some(source.to_string())
+            }
+            _ => Some(fingerprint.to_string()),
+        }
+    }
+
+    /// The origin derivation path written the way `format` asks, `m/84h/0h/0h` or `84'/0'/0'`,
+    /// single sig only like [`Descriptors::origin`]
+    pub fn origin_path_string(&self, format: PathFormat) -> Option<String> {
+        let (_, path) = self.origin()?;
+        Some(format.format_path(&path))
+    }
+
+    /// Checks the origin path against the standard one for the script type, `84'/0'/0'` for
+    /// P2WPKH
+    ///
+    /// The expected coin type is `0` for mainnet keys and `1` for testnet keys. Descriptors
+    /// synthesized from a bare xpub always use the standard path, see
+    /// [`Descriptors::is_synthesized`].
+    pub fn standard_conformance(&self) -> Conformance {
+        let (Some(script_type), Some((_, origin))) = (self.script_type(), self.origin()) else {
+            return Conformance::Unknown;
+        };
+
+        let hardened = |child: &ChildNumber| match child {
+            ChildNumber::Hardened { index } => Some(*index),
+            ChildNumber::Normal { .. } => None,
+        };
+
+        let path = origin.as_ref().iter().map(hardened).collect::<Vec<_>>();
+        let [Some(purpose), Some(coin_type), Some(account)] = path[..] else {
+            return Conformance::Custom;
+        };
+
+        if purpose != script_type.purpose() {
+            return Conformance::Custom;
+        }
+
+        let standard_coin_type = match self.extended_keys().first().map(|xpub| xpub.network) {
+            Some(NetworkKind::Test) => 1,
+            _ => 0,
+        };
+
+        let coin_type = (coin_type != standard_coin_type).then_some(coin_type);
+        let account = (account != 0).then_some(account);
+
+        match (coin_type, account) {
+            (None, None) => Conformance::Standard,
+            (coin_type, account) => Conformance::NonStandard { coin_type, account },
+        }
+    }
+
+    /// A multi-line summary for reviewing an import: script type, network, master fingerprints,
+    /// origin path, both descriptors and the first receive address
+    pub fn pretty_print(&self, network: Network) -> Result<String, Error> {
+        let script_type = match self.script_type() {
+            Some(script_type) => script_type.to_string(),
+            None if self.cosigner_count() > 1 => {
+                format!("Multisig, {} keys", self.cosigner_count())
+            }
+            None => "Unknown".to_string(),
+        };
+
+        let fingerprints = self
+            .fingerprints()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let origin_path = self
+            .origin_path_string(PathFormat::MASTER_APOSTROPHE)
+            .unwrap_or_else(|| "-".to_string());
+
+        let first_address = self.address_at(Keychain::External, 0, network)?;
+
+        let lines = [
+            ("Script type", script_type),
+            ("Network", network.to_string()),
+            ("Master fingerprint", fingerprints),
+            ("Origin path", origin_path),
+            ("External", self.external.to_string()),
+            ("Internal", self.internal.to_string()),
+            ("First address", first_address.to_string()),
+        ];
+
+        let lines = lines
+            .iter()
+            .map(|(label, value)| format!("{:<20}{value}", format!("{label}:")))
+            .collect::<Vec<_>>();
+
+        Ok(lines.join("\n"))
+    }
+
+    /// Master fingerprint of the only key in the descriptor, `None` unless it has exactly one key
+    ///
+    /// The key can be anywhere in the script, so a timelocked `wsh(and_v(v:pk(..),older(144)))`
+    /// wallet has a fingerprint too.
+    pub fn fingerprint(&self) -> Option<Fingerprint> {
+        let key = self.single_key().ok()?;
+        Some(key.master_fingerprint())
+    }
+
+    /// The extended key of the only key in the descriptor, wherever it is in the script
+    ///
+    /// Errors with [`Error::MultipleKeys`] for multisig and other scripts with more than one key.
+    pub fn xpub(&self) -> Result<bitcoin::bip32::Xpub, Error> {
+        let xpub: bitcoin::bip32::Xpub = match self.single_key()? {
+            DescriptorPublicKey::XPub(inner) => inner.xkey,
+            DescriptorPublicKey::MultiXPub(inner) => inner.xkey,
+            DescriptorPublicKey::Single(_) => return Err(Error::SinglePubkeyNotSupported),
+        };
+
+        Ok(xpub)
+    }
+
+    /// The one key in the receive descriptor, walking into miniscript fragments
+    fn single_key(&self) -> Result<&DescriptorPublicKey, Error> {
+        match wallet_keys(&self.external)[..] {
+            [key] => Ok(key),
+            [] => Err(Error::NoXpubInDescriptor),
+            ref keys => Err(Error::MultipleKeys(keys.len())),
+        }
+    }
+
+    /// The fingerprint of the account xpub itself, not the master fingerprint from the key origin
+    ///
+    /// Some tools identify keys by this fingerprint instead of the master fingerprint.
+    pub fn account_fingerprint(&self) -> Result<Fingerprint, Error> {
+        Ok(self.xpub()?.fingerprint())
+    }
+
+    /// The account key with the SLIP-132 prefix for its script type, a `zpub` for P2WPKH and a
+    /// `ypub` for P2SH-P2WPKH (`vpub` and `upub` on testnet)
+    ///
+    /// There is no SLIP-132 prefix for taproot, so P2TR keys are returned as the plain `xpub`,
+    /// the same as P2PKH.
+    pub fn slip132_xpub(&self) -> Result<String, Error> {
+        let script_type = self.script_type().ok_or(Error::NoXpubInDescriptor)?;
+        let xpub = match self.extended_keys()[..] {
+            [xpub] => xpub,
+            _ => return Err(Error::SinglePubkeyNotSupported),
+        };
+
+        Ok(xpub::slip132_encode(&xpub, script_type))
+    }
+
+    /// The account key with the prefix it was imported with, so a wallet imported from a `zpub`
+    /// shows a `zpub` again
+    ///
+    /// Keys from descriptors are always written as an `xpub` or `tpub`, so that is what they get.
+    pub fn xpub_in_original_format(&self) -> Result<String, Error> {
+        let xpub = match self.extended_keys()[..] {
+            [xpub] => xpub,
+            [] => return Err(Error::NoXpubInDescriptor),
+            _ => return Err(Error::SinglePubkeyNotSupported),
+        };
+
+        match self.original_format {
+            Some(format) => Ok(xpub::encode_with_format(&xpub, format)),
+            None => Ok(xpub.to_string()),
+        }
+    }
+
+    /// The BIP32 serialization of the xpub returned by [`Descriptors::xpub`]
+    pub fn xpub_bytes(&self) -> Result<[u8; 78], Error> {
+        Ok(self.xpub()?.encode())
+    }
+
+    /// Verify a signed message for the receive address at `address_index`
+    ///
+    /// Supports the legacy (Electrum / BIP137) base64 signatures used for P2PKH, P2SH-P2WPKH and
+    /// P2WPKH addresses. Other wallets, like taproot or multisig, are an
+    /// [`Error::MessageVerificationUnsupported`]. BIP322 signatures are not supported, they are an
+    /// [`Error::InvalidSignature`].
+    pub fn verify_message(
+        &self,
+        address_index: u32,
+        message: &str,
+        signature: &str,
+    ) -> Result<bool, Error> {
+        check_index(address_index, MAX_DERIVATION_INDEX)?;
+
+        match self.script_type() {
+            Some(ScriptType::P2pkh | ScriptType::P2shP2wpkh | ScriptType::P2wpkh) => {}
+            Some(ScriptType::P2tr) | None => return Err(Error::MessageVerificationUnsupported),
+        }
+
+        let script_pubkey = self
+            .external
+            .at_derivation_index(address_index)?
+            .script_pubkey();
+
+        let signature =
+            MessageSignature::from_base64(signature).map_err(Error::InvalidSignature)?;
+
+        let secp = &secp256k1::Secp256k1::verification_only();
+        let Ok(pubkey) = signature.recover_pubkey(secp, signed_msg_hash(message)) else {
+            return Ok(false);
+        };
+
+        // BIP137 segwit signatures set header bytes that don't map to the compressed flag, but
+        // segwit keys are always compressed
+        let wpubkey_hash = CompressedPublicKey(pubkey.inner).wpubkey_hash();
+        let p2wpkh = ScriptBuf::new_p2wpkh(&wpubkey_hash);
+
+        let signed_by = [
+            ScriptBuf::new_p2pkh(&pubkey.pubkey_hash()),
+            ScriptBuf::new_p2sh(&p2wpkh.script_hash()),
+            p2wpkh,
+        ];
+
+        Ok(signed_by.contains(&script_pubkey))
+    }
+}
+
+#[cfg(feature = "uniffi")]
+mod ffi {
+    use super::Descriptors;
+
+    impl Descriptors {
+        pub fn external(&self) -> String {
+            self.external.to_string()
+        }
+
+        pub fn internal(&self) -> String {
+            self.internal.to_string()
+        }
+    }
+}
+
+/// A watch only descriptor for a single address, `wpkh(02...)` or `pkh([817e7be0/84h/0h/0h/0/5]02...)`
+///
+/// [`Descriptors`] needs an extended key with receive and change keychains, this holds the one
+/// pubkey descriptor some users want to watch instead. There is nothing to derive, index 0 is the
+/// only address.
+#[derive(Debug, Clone, Serialize, Deserialize, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Object))]
+pub struct FixedDescriptors {
+    #[serde(
+        serialize_with = "serialize_descriptor",
+        deserialize_with = "deserialize_descriptor"
+    )]
+    pub descriptor: Descriptor<DescriptorPublicKey>,
+}
+
+impl FixedDescriptors {
+    pub fn try_from_line(line: &str) -> Result<Self, Error> {
+        let descriptor = SECP.with(|secp| parse_descriptor(secp, line.trim()))?;
+
+        let key = match &descriptor {
+            Descriptor::Pkh(pkh) => pkh.as_inner(),
+            Descriptor::Wpkh(wpkh) => wpkh.as_inner(),
+            _ => return Err(Error::NotFixedSinglePubkey),
+        };
+
+        match key {
+            DescriptorPublicKey::Single(SinglePub {
+                key: SinglePubKey::FullKey(key),
+                ..
+            }) if key.compressed => (),
+            _ => return Err(Error::NotFixedSinglePubkey),
+        }
+
+        Ok(Self { descriptor })
+    }
+
+    /// The only address of the descriptor
+    pub fn address(&self, network: Network) -> Result<Address, Error> {
+        self.address_at(0, network)
+    }
+
+    /// Same as [`Descriptors::address_at`], but only index 0 exists
+    pub fn address_at(&self, index: u32, network: Network) -> Result<Address, Error> {
+        if index != 0 {
+            return Err(Error::FixedIndex(index));
+        }
+
+        self.descriptor
+            .at_derivation_index(0)?
+            .address(network)
+            .map_err(Error::UnableToGetAddress)
+    }
+}
+
+impl TryFrom<WasabiJson> for Descriptors {
+    type Error = Error;
+
+    fn try_from(json: WasabiJson) -> Result<Self, Self::Error> {
+        let fingerprint = xpub::parse_xfp(&json.master_fingerprint, ByteOrder::BigEndian)?;
+        // older exports don't include the path, they are always the first native segwit account
+        let derivation_path = json
+            .account_key_path
+            .as_deref()
+            .map(strip_master_prefix)
+            .unwrap_or("84h/0h/0h");
+
+        let xpub = &json.ext_pub_key;
+
+        let script = multipath_key(fingerprint, derivation_path, xpub);
+        let desc = ScriptType::P2wpkh.wrap_with(&script);
+
+        let mut desc = Descriptors::try_from_generated_line(&desc)?;
+        desc.source_fingerprint = Some(json.master_fingerprint.trim().to_string());
+
+        Ok(desc)
+    }
+}
+
+impl TryFrom<SparrowSingleSigJson> for Descriptors {
+    type Error = Error;
+
+    fn try_from(json: SparrowSingleSigJson) -> Result<Self, Self::Error> {
+        let [keystore] = &json.keystores[..] else {
+            return Err(Error::NotSingleSig(json.keystores.len()));
+        };
+
+        let single_sig = SingleSig {
+            name: Some(json.script_type),
+            deriv: Some(keystore.key_derivation.derivation_path.clone()),
+            xpub: Some(keystore.extended_public_key.clone()),
+            ..Default::default()
+        };
+
+        let fingerprint = &keystore.key_derivation.master_fingerprint;
+        Self::try_from_single_sig(single_sig, Some(fingerprint))
+    }
+}
+
+impl TryFrom<Bitbox02Json> for Descriptors {
+    type Error = Error;
+
+    fn try_from(json: Bitbox02Json) -> Result<Self, Self::Error> {
+        let BitboxSimple {
+            script_type,
+            key_info,
+        } = json.bitcoin_simple;
+
+        let single_sig = SingleSig {
+            name: Some(script_type),
+            deriv: Some(key_info.keypath),
+            xpub: Some(key_info.xpub),
+            ..Default::default()
+        };
+
+        Descriptors::try_from_single_sig(single_sig, Some(&key_info.root_fingerprint))
+    }
+}
+
+impl TryFrom<LedgerLiveJson> for Descriptors {
+    type Error = Error;
+
+    fn try_from(json: LedgerLiveJson) -> Result<Self, Self::Error> {
+        if let Some(currency) = json.currency_id.as_ref().filter(|id| *id != "bitcoin") {
+            return Err(Error::UnsupportedCurrency(currency.clone()));
+        }
+
+        let script_type = json.script_type().ok_or(Error::MissingScriptType)?;
+        let deriv = format!("{}h/0h/{}h", script_type.purpose(), json.index);
+
+        let single_sig = SingleSig {
+            name: Some(script_type),
+            deriv: Some(deriv),
+            xpub: Some(json.xpub),
+            ..Default::default()
+        };
+
+        // ledger live doesn't export the master fingerprint
+        Descriptors::try_from_single_sig(single_sig, Some("00000000"))
+    }
+}
+
+impl TryFrom<ElectrumJson> for Descriptors {
+    type Error = Error;
+
+    fn try_from(json: ElectrumJson) -> Result<Self, Self::Error> {
+        let (desc, _warning) = Descriptors::try_from_electrum(json, false)?;
+        Ok(desc)
+    }
+}
+
+impl Descriptors {
+    /// Every active receive and change descriptor pair in Bitcoin Core's `listdescriptors` output
+    ///
+    /// A receive descriptor is paired with the change descriptor that uses the same keys. The
+    /// birthday of each pair is the earliest timestamp in the dump, `"now"` timestamps are
+    /// skipped since they are only used when importing and say nothing about the wallet's age.
+    pub fn try_from_bitcoin_core(json: BitcoinCoreJson) -> Result<Vec<Self>, Error> {
+        let birthday = json
+            .descriptors
+            .iter()
+            .filter_map(|desc| desc.timestamp.as_ref()?.unix())
+            .min();
+
+        let mut external = Vec::new();
+        let mut internal = Vec::new();
+
+        for desc in json.descriptors.iter().filter(|desc| desc.active) {
+            let parsed = SECP.with(|secp| parse_descriptor(secp, &desc.desc))?;
+            match desc.internal {
+                true => internal.push((parsed, &desc.desc)),
+                false => external.push((parsed, &desc.desc)),
+            }
+        }
+
+        let mut pairs = Vec::new();
+        for (external, original_external) in external {
+            let pair = internal.iter().find_map(|(internal, original_internal)| {
+                let desc = Descriptors {
+                    external: external.clone(),
+                    internal: internal.clone(),
+                    original_external: Some(original_external.to_string()),
+                    original_internal: Some(original_internal.to_string()),
+                    birthday,
+                    original_format: None,
+                    source_fingerprint: None,
+                };
+
+                // the pair has to be the same descriptor with a different keychain step
+                let multipath = desc.to_multipath_line().ok()?;
+                let reparsed = Descriptors::try_from_line(&multipath).ok()?;
+                (reparsed == desc).then_some(desc)
+            });
+
+            pairs.extend(pair);
+        }
+
+        if pairs.is_empty() {
+            return Err(Error::NoActiveDescriptors);
+        }
+
+        Ok(pairs)
+    }
+}
+
+impl TryFrom<&str> for Descriptors {
+    type Error = Error;
+
+    fn try_from(desc: &str) -> Result<Self, Self::Error> {
+        // check before splitting so a huge paste is rejected without collecting it first
+        if desc.len() > MAX_INPUT_BYTES || desc.lines().nth(MAX_INPUT_LINES).is_some() {
+            return Err(Error::InputTooLarge);
+        }
+
+        let lines = desc
+            .trim()
+            .split('\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| line.trim())
+            .collect::<Vec<&str>>();
+
+        if let Some(line) = lines.first() {
+            // json descriptor
+            if line.trim().starts_with('{') {
+                let json: JsonDescriptor =
+                    serde_json::from_str(desc).map_err(Error::InvalidJsonDescriptor)?;
+                return Self::try_from_line(&json.descriptor);
+            }
+
+            // json array of descriptor strings, one multipath or a receive and change pair, a
+            // key expression also starts with `[` but isn't json
+            if line.starts_with('[') {
+                if let Ok(lines) = serde_json::from_str::<Vec<String>>(desc) {
+                    return Self::try_from(lines.join("\n").as_str());
+                }
+            }
+        }
+
+        match lines.len() {
+            1 => Descriptors::try_from_line(lines[0]),
+            2 => {
+                let external = lines[0];
+                let internal = lines[1];
+
+                let (internal_desc, external_desc) = SECP.with(|secp| {
+                    Ok::<_, Error>((
+                        parse_descriptor(secp, internal)?,
+                        parse_descriptor(secp, external)?,
+                    ))
+                })?;
+
+                Ok(Descriptors {
+                    external: external_desc,
+                    internal: internal_desc,
+                    original_external: Some(external.to_string()),
+                    original_internal: Some(internal.to_string()),
+                    birthday: None,
+                    original_format: None,
+                    source_fingerprint: None,
+                })
+            }
+            0 => Err(Error::MissingDescriptor),
+            n => Err(Error::TooManyKeys(n)),
+        }
+    }
+}
+
+/// Errors if `stated` isn't the master fingerprint of `xpub`
+///
+/// The master fingerprint can only be computed for the master key itself or its direct children,
+/// where it is the parent fingerprint. Deeper keys, like account xpubs, only record their parent's
+/// fingerprint, so they are an [`Error::FingerprintUnverifiable`] instead of passing unchecked.
+pub fn verify_master_fingerprint(stated: Fingerprint, xpub: &xpub::Xpub) -> Result<(), Error> {
+    let computed = match xpub.depth() {
+        0 => xpub.self_fingerprint(),
+        1 => xpub.master_fingerprint(),
+        depth => return Err(Error::FingerprintUnverifiable(depth)),
+    };
+
+    if stated != computed {
+        return Err(Error::FingerprintXpubMismatch { stated, computed });
+    }
+
+    Ok(())
+}
+
+/// Master fingerprint of an Electrum keystore, from the Coldcard fields when it has them
+pub(crate) fn electrum_fingerprint(
+    keystore: &Keystore,
+    xpub: &xpub::Xpub,
+) -> Result<Fingerprint, xpub::Error> {
+    let fingerprint = match (&keystore.ckcc_xfp, &keystore.ckcc_xpub) {
+        (Some(xfp), _) => xpub::fingerprint_from_u32(*xfp, ByteOrder::LittleEndian),
+        (None, Some(ck_xpub)) => xpub::xpub_to_fingerprint(ck_xpub)?,
+        (None, None) => xpub.master_fingerprint(),
+    };
+
+    Ok(fingerprint)
+}
+
+/// The descriptor with its hardened steps written with `marker` and a checksum to match
+///
+/// miniscript always writes `'`, which only ever appears as a hardened marker, so swapping it
+/// can't touch a key or a script name.
+fn with_marker(descriptor: &Descriptor<DescriptorPublicKey>, marker: HardenedMarker) -> String {
+    let body = format!("{descriptor:#}");
+    let body = match marker {
+        HardenedMarker::Apostrophe => body,
+        HardenedMarker::H => body.replace('\'', "h"),
+    };
+
+    let checksum = desc_checksum(&body)
+        .expect("descriptors written by miniscript only use checksum characters");
+    format!("{body}#{checksum}")
+}
+
+/// Key expression with its origin covering both keychains, `[fingerprint/path]xpub/<0;1>/*`
+pub(crate) fn multipath_key(
+    fingerprint: impl std::fmt::Display,
+    derivation_path: impl std::fmt::Display,
+    xpub: impl std::fmt::Display,
+) -> String {
+    keychains_key(fingerprint, derivation_path, xpub, 0, 1)
+}
+
+/// Key expression with its origin and custom keychains, `[fingerprint/path]xpub/<receive;change>/*`
+///
+/// An empty path, the origin of a master key, is written as just `[fingerprint]`.
+fn keychains_key(
+    fingerprint: impl std::fmt::Display,
+    derivation_path: impl std::fmt::Display,
+    xpub: impl std::fmt::Display,
+    receive: u32,
+    change: u32,
+) -> String {
+    let derivation_path = derivation_path.to_string();
+    let origin = match strip_master_prefix(&derivation_path) {
+        "" | "m" => fingerprint.to_string(),
+        path => format!("{fingerprint}/{path}"),
+    };
+
+    format!("[{origin}]{xpub}/<{receive};{change}>/*")
+}
+
+/// Errors for indexes above `max_index`, before miniscript fails on them with a less clear error
+pub(crate) fn check_index(index: u32, max_index: u32) -> Result<(), Error> {
+    match index <= max_index {
+        true => Ok(()),
+        false => Err(Error::IndexOutOfRange(index)),
+    }
+}
+
+/// All the keys in the descriptor, in the order they appear
+fn descriptor_keys(descriptor: &Descriptor<DescriptorPublicKey>) -> Vec<&DescriptorPublicKey> {
+    let mut keys = Vec::new();
+    descriptor.for_each_key(|key| {
+        keys.push(key);
+        true
+    });
+
+    keys
+}
+
+/// The keys that belong to the wallet, without the unspendable internal key of a taproot
+/// multisig, which no cosigner holds
+fn wallet_keys(descriptor: &Descriptor<DescriptorPublicKey>) -> Vec<&DescriptorPublicKey> {
+    descriptor_keys(descriptor)
+        .into_iter()
+        .filter(|key| !is_nums_key(key))
+        .collect()
 }
 
-fn deserialize_descriptor<'de, D>(
-    deserializer: D,
-) -> Result<Descriptor<DescriptorPublicKey>, D::Error>
-where
-    D: serde::Deserializer<'de>,
-{
-    let secp = &secp256k1::Secp256k1::signing_only();
-    let desc = String::deserialize(deserializer)?;
-    let (descriptor, _) = Descriptor::<DescriptorPublicKey>::parse_descriptor(secp, desc.as_str())
-        .map_err(serde::de::Error::custom)?;
+fn is_nums_key(key: &DescriptorPublicKey) -> bool {
+    match key {
+        DescriptorPublicKey::Single(SinglePub {
+            key: SinglePubKey::XOnly(key),
+            ..
+        }) => key.to_string() == crate::multisig::NUMS_INTERNAL_KEY,
+        _ => false,
+    }
+}
+
+/// Merges each external key with the matching internal key into a `<external;internal>` key
+struct MultipathTranslator<'a> {
+    internal: Vec<&'a DescriptorPublicKey>,
+}
+
+impl Translator<DescriptorPublicKey, DescriptorPublicKey, ()> for MultipathTranslator<'_> {
+    fn pk(&mut self, key: &DescriptorPublicKey) -> Result<DescriptorPublicKey, ()> {
+        let DescriptorPublicKey::XPub(external) = key else {
+            return Ok(key.clone());
+        };
+
+        let internal = self
+            .internal
+            .iter()
+            .find_map(|key| match key {
+                DescriptorPublicKey::XPub(internal)
+                    if internal.xkey == external.xkey && internal.origin == external.origin =>
+                {
+                    Some(internal)
+                }
+                _ => None,
+            })
+            .ok_or(())?;
+
+        let derivation_paths = DerivPaths::new(vec![
+            external.derivation_path.clone(),
+            internal.derivation_path.clone(),
+        ])
+        .ok_or(())?;
+
+        Ok(DescriptorPublicKey::MultiXPub(DescriptorMultiXKey {
+            origin: external.origin.clone(),
+            xkey: external.xkey,
+            derivation_paths,
+            wildcard: external.wildcard,
+        }))
+    }
+
+    translate_hash_clone!(DescriptorPublicKey, DescriptorPublicKey, ());
+}
+
+/// Swaps the final `/0/*` of every key for `/1/*`, the change keychain of the same key
+struct ChangeKeychainTranslator;
+
+impl Translator<DescriptorPublicKey, DescriptorPublicKey, Error> for ChangeKeychainTranslator {
+    fn pk(&mut self, key: &DescriptorPublicKey) -> Result<DescriptorPublicKey, Error> {
+        let not_receive = || Error::NotReceiveKeychain(key.to_string());
+
+        let DescriptorPublicKey::XPub(xkey) = key else {
+            return Err(not_receive());
+        };
+
+        let path = xkey.derivation_path.as_ref();
+        let (Some(ChildNumber::Normal { index: 0 }), Wildcard::Unhardened) =
+            (path.last(), xkey.wildcard)
+        else {
+            return Err(not_receive());
+        };
+
+        let mut xkey = xkey.clone();
+        let mut path = path.to_vec();
+        path.pop();
+        path.push(ChildNumber::Normal { index: 1 });
+        xkey.derivation_path = path.into();
+
+        Ok(DescriptorPublicKey::XPub(xkey))
+    }
+
+    translate_hash_clone!(DescriptorPublicKey, DescriptorPublicKey, Error);
+}
+
+/// Sets the master fingerprint of keys that have the `00000000` placeholder
+struct FingerprintTranslator {
+    fingerprint: Fingerprint,
+}
+
+impl Translator<DescriptorPublicKey, DescriptorPublicKey, Error> for FingerprintTranslator {
+    fn pk(&mut self, key: &DescriptorPublicKey) -> Result<DescriptorPublicKey, Error> {
+        let mut key = key.clone();
+        let origin = match &mut key {
+            DescriptorPublicKey::XPub(xkey) => &mut xkey.origin,
+            DescriptorPublicKey::MultiXPub(xkey) => &mut xkey.origin,
+            DescriptorPublicKey::Single(single) => &mut single.origin,
+        };
+
+        match origin {
+            Some((existing, _)) if existing.as_bytes() == &[0; 4] => *existing = self.fingerprint,
+            Some((existing, _)) if *existing != self.fingerprint => {
+                return Err(Error::FingerprintConflict {
+                    existing: *existing,
+                    new: self.fingerprint,
+                })
+            }
+            _ => (),
+        }
+
+        Ok(key)
+    }
+
+    translate_hash_clone!(DescriptorPublicKey, DescriptorPublicKey, Error);
+}
+
+/// The single sig descriptor for `script_type` around `key`
+fn script_descriptor(
+    key: DescriptorPublicKey,
+    script_type: ScriptType,
+) -> Result<Descriptor<DescriptorPublicKey>, Error> {
+    let descriptor = match script_type {
+        ScriptType::P2pkh => Descriptor::new_pkh(key)?,
+        ScriptType::P2shP2wpkh => Descriptor::new_sh_wpkh(key)?,
+        ScriptType::P2wpkh => Descriptor::new_wpkh(key)?,
+        ScriptType::P2tr => Descriptor::new_tr(key, None)?,
+    };
+
+    Ok(descriptor)
+}
+
+fn translate_error(error: TranslateErr<Error>) -> Error {
+    match error {
+        TranslateErr::TranslatorErr(error) => error,
+        TranslateErr::OuterError(error) => Error::InvalidDescriptorParse(error),
+    }
+}
+
+fn parse_descriptor<C: secp256k1::Signing>(
+    secp: &secp256k1::Secp256k1<C>,
+    line: &str,
+) -> Result<Descriptor<DescriptorPublicKey>, Error> {
+    // miniscript can't parse BIP390 key aggregation yet and fails with a confusing key error
+    if line.contains("musig(") {
+        return Err(Error::MuSigNotSupported);
+    }
+
+    let line = strip_comment(line);
+    let line = lowercase_checksum(&line);
+    let line = strip_origin_master_prefix(&line)?;
+
+    // a multipath xprv fails to parse with a confusing error, so look for one first
+    if has_extended_private_key(&line) {
+        return Err(Error::PrivateKeyNotAllowed);
+    }
+
+    let (descriptor, keymap) = Descriptor::<DescriptorPublicKey>::parse_descriptor(secp, &line)?;
+
+    // miniscript swaps each xprv or WIF key for its public key and hands the secrets back
+    if !keymap.is_empty() {
+        return Err(Error::PrivateKeyNotAllowed);
+    }
+
+    Ok(descriptor)
+}
+
+/// Whether any word of the descriptor is a BIP32 extended private key, whatever its prefix
+fn has_extended_private_key(line: &str) -> bool {
+    line.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| word.len() > 100)
+        .filter_map(|word| bitcoin::base58::decode_check(word).ok())
+        // private key data is a zero byte then the 32 byte secret, public keys start with 2 or 3
+        .any(|data| data.len() == 78 && data[45] == 0)
+}
+
+/// Characters a BIP380 checksum is written with
+const CHECKSUM_CHARSET: &str = "qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// Descriptor backups are sometimes annotated, `wpkh(...) # my cold wallet`, which miniscript
+/// would read as a bad checksum
+///
+/// Checksum characters written right after the `#` are always left for miniscript to check, so
+/// a wrong or cut off checksum is still an error. The fragment is only a comment when whitespace
+/// follows the `#` or it has characters that can't be in a checksum, then it's dropped and the
+/// descriptor is parsed without a checksum. Text after a checksum is dropped too.
+fn strip_comment(line: &str) -> Cow<'_, str> {
+    let Some((body, fragment)) = line.split_once('#') else {
+        return Cow::Borrowed(line);
+    };
+
+    let checksum = fragment
+        .split_once(char::is_whitespace)
+        .map_or(fragment, |(checksum, _)| checksum);
+
+    let is_checksum = !checksum.is_empty()
+        && checksum
+            .chars()
+            .all(|c| CHECKSUM_CHARSET.contains(c.to_ascii_lowercase()));
+
+    match is_checksum {
+        true if checksum == fragment => Cow::Borrowed(line),
+        true => Cow::Owned(format!("{}#{checksum}", body.trim_end())),
+        false => Cow::Owned(body.trim_end().to_string()),
+    }
+}
+
+/// BIP380 checksums are lowercase, but hand edited descriptors sometimes have them uppercased
+///
+/// Only the 8 checksum characters after the `#` are lowercased, the descriptor is left alone
+fn lowercase_checksum(line: &str) -> Cow<'_, str> {
+    match line.split_once('#') {
+        Some((body, checksum))
+            if checksum.len() == 8 && checksum.chars().any(|c| c.is_ascii_uppercase()) =>
+        {
+            Cow::Owned(format!("{body}#{}", checksum.to_ascii_lowercase()))
+        }
+        _ => Cow::Borrowed(line),
+    }
+}
+
+/// Strips the leading `m/` from a derivation path, `m/84h/0h/0h` becomes `84h/0h/0h`
+fn strip_master_prefix(path: &str) -> &str {
+    path.strip_prefix("m/").unwrap_or(path)
+}
+
+/// Errors if `line` has no `#checksum`, or if it isn't the BIP380 checksum of the descriptor
+/// before it
+pub fn verify_checksum(line: &str) -> Result<(), Error> {
+    let (body, checksum) = line.trim().split_once('#').ok_or(Error::MissingChecksum)?;
+
+    let expected = desc_checksum(body)?;
+    if checksum != expected {
+        return Err(Error::InvalidChecksum {
+            expected,
+            found: checksum.to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+/// BIP380 key origins don't include `m/`, but some tools emit `[817e7be0/m/84h/0h/0h]`
+///
+/// Removes the `m/` from every key origin, the checksum (if any) is checked against the line as
+/// given and then dropped, because it no longer matches the normalized line
+fn strip_origin_master_prefix(line: &str) -> Result<Cow<'_, str>, Error> {
+    if !line.contains("/m/") && !line.contains("/m]") {
+        return Ok(Cow::Borrowed(line));
+    }
+
+    let body = match line.split_once('#') {
+        Some((body, _)) => {
+            verify_checksum(line)?;
+            body
+        }
+        None => line,
+    };
+
+    let mut normalized = String::with_capacity(body.len());
+    let mut rest = body;
+
+    while let Some(start) = rest.find('[') {
+        let (before, origin) = rest.split_at(start);
+        let end = origin.find(']').map(|end| end + 1).unwrap_or(origin.len());
+        let (origin, after) = origin.split_at(end);
+
+        normalized.push_str(before);
+        match origin[1..].split_once('/') {
+            Some((fingerprint, path)) if path.starts_with("m/") || path == "m]" => {
+                normalized.push('[');
+                normalized.push_str(fingerprint);
+                // drop the `m`, keeping the `/` or `]` that follows it
+                normalized.push_str(&path[1..]);
+            }
+            _ => normalized.push_str(origin),
+        }
+
+        rest = after;
+    }
+
+    normalized.push_str(rest);
+    Ok(Cow::Owned(normalized))
+}
+
+fn serialize_descriptor<S>(
+    descriptor: &Descriptor<DescriptorPublicKey>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    let desc = descriptor.to_string();
+    serializer.serialize_str(&desc)
+}
+
+fn deserialize_descriptor<'de, D>(
+    deserializer: D,
+) -> Result<Descriptor<DescriptorPublicKey>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let desc = String::deserialize(deserializer)?;
+    let (descriptor, keymap) = SECP
+        .with(|secp| Descriptor::<DescriptorPublicKey>::parse_descriptor(secp, desc.as_str()))
+        .map_err(serde::de::Error::custom)?;
+
+    if !keymap.is_empty() {
+        return Err(serde::de::Error::custom(Error::PrivateKeyNotAllowed));
+    }
+
+    Ok(descriptor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn known_desc() -> Descriptors {
+        let known_desc = "wpkh([817e7be0/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>/*)#60tjs4c7";
+        Descriptors::try_from_line(known_desc).unwrap()
+    }
+
+    #[test]
+    fn test_parse_combination_descriptor() {
+        let secp = &secp256k1::Secp256k1::signing_only();
+        let descriptor = "wpkh([817e7be0/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>/*)#60tjs4c7";
+        let desc = Descriptors::try_from_line(descriptor);
+
+        assert!(desc.is_ok());
+        let desc = desc.unwrap();
+
+        let (external, _) = Descriptor::parse_descriptor(secp, "wpkh([817e7be0/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/0/*)#sqx4cjta").unwrap();
+        let (internal, _) = Descriptor::parse_descriptor(secp, "wpkh([817e7be0/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/1/*)#p5r598m9").unwrap();
+
+        assert_eq!(desc.external, external);
+        assert_eq!(desc.internal, internal);
+    }
+
+    #[test]
+    fn test_fingerprint_getter() {
+        let single_sig = r#"{
+    "name": "p2wpkh",
+    "xfp": "8DFECFC3",
+    "deriv": "m/84h/0h/0h",
+    "xpub": "xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM",
+    "desc": "wpkh([817e7be0/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>/*)#60tjs4c7",
+    "_pub": "zpub6rNrPrFwgm4wMBSysetK5tpLBS2HYT8TDKQA6amxFHKJUnQq8rNtc4JDfGYPbvF9wJyagPpG1Faqnfe3BB8XzKon8LwW9KkMWyAQ4RQHzB1",
+    "first": "bc1q0g0vn4yqyk0zjwxw0zv5pltyyczty004zc9g7r"
+        }"#;
+
+        let single_sig: SingleSig = serde_json::from_str(single_sig).unwrap();
+        let parse_desc = Descriptors::try_from_single_sig(single_sig, None).unwrap();
+
+        assert_eq!(
+            parse_desc
+                .fingerprint()
+                .unwrap()
+                .to_string()
+                .to_uppercase()
+                .as_str(),
+            "817E7BE0"
+        );
+    }
+
+    #[test]
+    fn test_parse_without_descriptor() {
+        let single_sig = r#"{
+    "name": "p2wpkh",
+    "xfp": "8DFECFC3",
+    "deriv": "m/84h/0h/0h",
+    "xpub": "xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM",
+    "_pub": "zpub6rNrPrFwgm4wMBSysetK5tpLBS2HYT8TDKQA6amxFHKJUnQq8rNtc4JDfGYPbvF9wJyagPpG1Faqnfe3BB8XzKon8LwW9KkMWyAQ4RQHzB1",
+    "first": "bc1q0g0vn4yqyk0zjwxw0zv5pltyyczty004zc9g7r"
+        }"#;
+
+        let single_sig: SingleSig = serde_json::from_str(single_sig).unwrap();
+
+        let parse_desc = Descriptors::try_from_single_sig(single_sig, Some("817E7BE0"));
+
+        assert!(parse_desc.is_ok());
+        let parse_desc = parse_desc.unwrap();
+
+        assert_eq!(known_desc().external, parse_desc.external);
+        assert_eq!(known_desc().internal, parse_desc.internal);
+
+        assert_eq!(
+            parse_desc.external.to_string(),
+            known_desc().external.to_string()
+        );
+
+        assert_eq!(
+            parse_desc.internal.to_string(),
+            known_desc().internal.to_string()
+        );
+    }
+
+    #[test]
+    fn test_parse_wasabi() {
+        let json = r#"{
+            "ColdCardFirmwareVersion": "5.4.0",
+            "MasterFingerprint": "817E7BE0",
+            "ExtPubKey": "xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM"
+        }"#;
+
+        let json = serde_json::from_str::<WasabiJson>(json).unwrap();
+        let desc = Descriptors::try_from(json);
+
+        assert!(desc.is_ok());
+        let desc = desc.unwrap();
+
+        assert_eq!(desc.external, known_desc().external);
+        assert_eq!(desc.internal, known_desc().internal);
+    }
+
+    #[test]
+    fn test_to_wasabi_json() {
+        let string = std::fs::read_to_string("test/data/new-wasabi.json").unwrap();
+        let desc =
+            Descriptors::try_from(serde_json::from_str::<WasabiJson>(&string).unwrap()).unwrap();
+
+        let exported = desc.to_wasabi_json().unwrap();
+        assert_eq!(exported.master_fingerprint, "817E7BE0");
+        assert_eq!(exported.account_key_path, None);
+
+        let json = serde_json::to_string(&exported).unwrap();
+        assert!(!json.contains("AccountKeyPath"));
+
+        let reimported = serde_json::from_str::<WasabiJson>(&json).unwrap();
+        assert_eq!(Descriptors::try_from(reimported).unwrap(), desc);
+
+        // other accounts keep their path
+        let xpub = "xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM";
+        let desc = Descriptors::try_from_line(&format!("wpkh([817e7be0/84h/0h/1h]{xpub}/<0;1>/*)"))
+            .unwrap();
+        let exported = desc.to_wasabi_json().unwrap();
+        assert_eq!(exported.account_key_path.as_deref(), Some("m/84'/0'/1'"));
+        assert_eq!(Descriptors::try_from(exported).unwrap(), desc);
+
+        let not_wasabi = [
+            format!("pkh([817e7be0/44h/0h/0h]{xpub}/<0;1>/*)"),
+            format!("wpkh([817e7be0/84h/0h/0h]{xpub}/<0;2>/*)"),
+            format!("wpkh({xpub}/<0;1>/*)"),
+        ];
+        for line in not_wasabi {
+            let desc = Descriptors::try_from_line(&line).unwrap();
+            assert!(
+                matches!(desc.to_wasabi_json(), Err(Error::NotWasabiCompatible)),
+                "{line}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_electrum() {
+        let json = r#"{
+            "seed_version": 17,
+            "use_encryption": false,
+            "wallet_type": "standard",
+            "keystore": {
+                "type": "hardware",
+                "hw_type": "coldcard",
+                "label": "Coldcard Import 817E7BE0",
+                "ckcc_xfp": 3766189697,
+                "ckcc_xpub": "xpub661MyMwAqRbcFFr2SGY3dUn7g8P9VKNZdKWL2Z2pZMEkBWH2D1KTcwTn7keZQCaScCx7BUDjHFJJHnzBvDgUFgNjYsQTRvo7LWfYEtt78Pb",
+                "derivation": "m/84h/0h/0h",
+                "xpub": "zpub6rNrPrFwgm4wMBSysetK5tpLBS2HYT8TDKQA6amxFHKJUnQq8rNtc4JDfGYPbvF9wJyagPpG1Faqnfe3BB8XzKon8LwW9KkMWyAQ4RQHzB1"
+            }
+        }"#;
+
+        let electrum = serde_json::from_str::<ElectrumJson>(json);
+        assert!(electrum.is_ok());
+
+        let electrum = electrum.unwrap();
+        let desc = Descriptors::try_from(electrum);
+
+        assert!(desc.is_ok());
+        let desc = desc.unwrap();
+
+        assert_eq!(desc.external, known_desc().external);
+        assert_eq!(desc.internal, known_desc().internal);
+    }
+
+    #[test]
+    fn test_parse_electrum_without_xfp() {
+        let json = r#"{
+            "seed_version": 17,
+            "use_encryption": false,
+            "wallet_type": "standard",
+            "keystore": {
+                "type": "hardware",
+                "hw_type": "coldcard",
+                "label": "Coldcard Import 817E7BE0",
+                "ckcc_xpub": "xpub661MyMwAqRbcFFr2SGY3dUn7g8P9VKNZdKWL2Z2pZMEkBWH2D1KTcwTn7keZQCaScCx7BUDjHFJJHnzBvDgUFgNjYsQTRvo7LWfYEtt78Pb",
+                "derivation": "m/84h/0h/0h",
+                "xpub": "zpub6rNrPrFwgm4wMBSysetK5tpLBS2HYT8TDKQA6amxFHKJUnQq8rNtc4JDfGYPbvF9wJyagPpG1Faqnfe3BB8XzKon8LwW9KkMWyAQ4RQHzB1"
+            }
+        }"#;
+
+        let electrum = serde_json::from_str::<ElectrumJson>(json);
+        assert!(electrum.is_ok());
+
+        let electrum = electrum.unwrap();
+        let desc = Descriptors::try_from(electrum);
+
+        assert!(desc.is_ok());
+        let desc = desc.unwrap();
+
+        assert_eq!(desc.external, known_desc().external);
+        assert_eq!(desc.internal, known_desc().internal);
+    }
+
+    #[test]
+    fn test_parse_electrum_without_ckcc() {
+        let json = r#"{
+            "seed_version": 17,
+            "use_encryption": false,
+            "wallet_type": "standard",
+            "keystore": {
+                "type": "hardware",
+                "hw_type": "coldcard",
+                "label": "Coldcard Import 817E7BE0",
+                "derivation": "m/84h/0h/0h",
+                "xpub": "zpub6rNrPrFwgm4wMBSysetK5tpLBS2HYT8TDKQA6amxFHKJUnQq8rNtc4JDfGYPbvF9wJyagPpG1Faqnfe3BB8XzKon8LwW9KkMWyAQ4RQHzB1"
+            }
+        }"#;
+
+        let electrum = serde_json::from_str::<ElectrumJson>(json);
+        assert!(electrum.is_ok());
+
+        let electrum = electrum.unwrap();
+        let desc = Descriptors::try_from(electrum);
+
+        assert!(desc.is_ok());
+        let desc = desc.unwrap();
+
+        let known_desc = "wpkh([90645a28/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>/*)#ujst24qf";
+        let known_desc = Descriptors::try_from_line(known_desc).unwrap();
+
+        assert_eq!(desc.external, known_desc.external);
+        assert_eq!(desc.internal, known_desc.internal);
+    }
+
+    #[test]
+    fn test_from_descriptors_file() {
+        let desc = r#"
+            wpkh([817e7be0/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/0/*)#sqx4cjta
+            wpkh([817e7be0/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/1/*)#p5r598m9
+        "#;
+
+        let desc = Descriptors::try_from(desc).unwrap();
+
+        assert_eq!(desc.external.to_string(), known_desc().external.to_string());
+        assert_eq!(desc.internal.to_string(), known_desc().internal.to_string());
+    }
+
+    #[test]
+    fn test_input_too_large() {
+        let line = known_desc().external.to_string();
+
+        let too_many_lines = vec![line.as_str(); MAX_INPUT_LINES + 1].join("\n");
+        let desc = Descriptors::try_from(too_many_lines.as_str());
+        assert!(matches!(desc, Err(Error::InputTooLarge)));
+
+        let too_many_bytes = "a".repeat(MAX_INPUT_BYTES + 1);
+        let desc = Descriptors::try_from(too_many_bytes.as_str());
+        assert!(matches!(desc, Err(Error::InputTooLarge)));
+    }
+
+    #[test]
+    fn test_original_string() {
+        let line = "wpkh([817e7be0/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>/*)";
+        let desc = Descriptors::try_from_line(line).unwrap();
+
+        assert_eq!(desc.original_string(Keychain::External), Some(line));
+        assert_eq!(desc.original_string(Keychain::Internal), Some(line));
+        assert_ne!(desc.external.to_string(), line);
+
+        let file = std::fs::read_to_string("test/data/descriptor-4.txt").unwrap();
+        let lines = file.lines().map(str::trim).collect::<Vec<_>>();
+        let desc = Descriptors::try_from(file.as_str()).unwrap();
+
+        assert_eq!(desc.original_string(Keychain::External), Some(lines[0]));
+        assert_eq!(desc.original_string(Keychain::Internal), Some(lines[1]));
+    }
+
+    #[test]
+    fn test_no_original_string_when_built() {
+        let json = std::fs::read_to_string("test/data/new-wasabi.json").unwrap();
+        let json = serde_json::from_str::<WasabiJson>(&json).unwrap();
+        let desc = Descriptors::try_from(json).unwrap();
+
+        assert_eq!(desc.original_string(Keychain::External), None);
+        assert_eq!(desc, known_desc());
+    }
+
+    #[test]
+    fn test_xpub_output() {
+        let know_desc = known_desc();
+        let xpub = know_desc.xpub();
+
+        assert!(xpub.is_ok());
+        assert!(xpub.unwrap().to_string().starts_with("xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM"));
+    }
+
+    #[test]
+    fn test_xpub_bytes() {
+        let bytes = known_desc().xpub_bytes().unwrap();
+        let xpub = bitcoin::bip32::Xpub::decode(&bytes).unwrap();
+
+        assert_eq!(xpub, known_desc().xpub().unwrap());
+    }
+
+    #[test]
+    fn test_verify_message() {
+        use bitcoin::{bip32::Xpriv, hashes::Hash as _};
+
+        let secp = &secp256k1::Secp256k1::new();
+        let master = Xpriv::new_master(Network::Bitcoin, &[1; 32]).unwrap();
+        let account_path = DerivationPath::from_str("m/84h/0h/0h").unwrap();
+        let account = master.derive_priv(secp, &account_path).unwrap();
+
+        let xpub = bitcoin::bip32::Xpub::from_priv(secp, &account);
+        let key = multipath_key(master.fingerprint(secp), &account_path, xpub);
+        let desc = Descriptors::try_from_line(&format!("wpkh({key})")).unwrap();
+
+        let path = DerivationPath::from_str("m/84h/0h/0h/0/1").unwrap();
+        let private_key = master.derive_priv(secp, &path).unwrap().private_key;
+        let message = "pubport verify";
+
+        let digest = secp256k1::Message::from_digest(signed_msg_hash(message).to_byte_array());
+        let signature = secp.sign_ecdsa_recoverable(&digest, &private_key);
+        let signature = MessageSignature::new(signature, true).to_base64();
+
+        assert!(desc.verify_message(1, message, &signature).unwrap());
+        assert!(!desc.verify_message(0, message, &signature).unwrap());
+        assert!(!desc.verify_message(1, "other message", &signature).unwrap());
+
+        let invalid = desc.verify_message(1, message, "not base64");
+        assert!(matches!(invalid, Err(Error::InvalidSignature(_))));
+
+        // the BIP322 test vector signature of "Hello World"
+        let bip322 = "AkcwRAIgZRfIY3p7/DoVTty6YZbWS71bc5Vct9p9Fia83eRmw2QCICK/ENGfwLtptFluMGs2KsqoNSk89pO7F29zJLUx9a/sASECx/EgAxlkQpQ9hYjgGu6EBCPMVPwVIVJqO4XCsMvViHI=";
+        let bip322 = desc.verify_message(1, "Hello World", bip322);
+        assert!(matches!(bip322, Err(Error::InvalidSignature(_))));
+
+        // taproot wallets can't sign legacy messages, instead of a signature that never verifies
+        let taproot = Descriptors::try_from_line(&format!("tr({key})")).unwrap();
+        assert!(matches!(
+            taproot.verify_message(1, message, &signature),
+            Err(Error::MessageVerificationUnsupported)
+        ));
+    }
+
+    #[test]
+    fn test_get_master_fingerprint() {
+        let know_desc = known_desc();
+        let master_fingerprint = know_desc.fingerprint().unwrap();
+        assert_eq!(master_fingerprint.to_string().as_str(), "817e7be0");
+    }
+
+    #[test]
+    fn test_origin_with_master_prefix() {
+        let descriptor = "wpkh([817e7be0/m/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>/*)";
+        let desc = Descriptors::try_from_line(descriptor).unwrap();
+
+        assert_eq!(desc.external, known_desc().external);
+        assert_eq!(desc.internal, known_desc().internal);
+    }
+
+    #[test]
+    fn test_origin_with_master_prefix_checks_checksum() {
+        let body = "wpkh([817e7be0/m/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>/*)";
+        let checksum = desc_checksum(body).unwrap();
+
+        let desc = Descriptors::try_from_line(&format!("{body}#{checksum}"));
+        assert!(desc.is_ok());
+
+        let desc = Descriptors::try_from_line(&format!("{body}#60tjs4c7"));
+        assert!(matches!(desc, Err(Error::InvalidChecksum { .. })));
+    }
+
+    #[test]
+    fn test_uppercase_checksum() {
+        let descriptor = "wpkh([817e7be0/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>/*)#60TJS4C7";
+        let desc = Descriptors::try_from_line(descriptor).unwrap();
+
+        assert_eq!(desc.external, known_desc().external);
+        assert_eq!(desc.internal, known_desc().internal);
+
+        let wrong = "wpkh([817e7be0/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>/*)#60TJS4C8";
+        assert!(Descriptors::try_from_line(wrong).is_err());
+    }
+
+    #[test]
+    fn test_cosigner_count() {
+        assert_eq!(known_desc().cosigner_count(), 1);
+
+        let multisig = "wsh(sortedmulti(2,[817e7be0/48h/0h/0h/2h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>/*,[90645a28/48h/0h/0h/2h]xpub6CCKAvUTNursEnaJ8k1d27LfqEUzeAx2N9wFqYE3W1xh7nqgJEBEbLSSmohwDxzsSvcsYqiQqFzRvta65Njbe5o84bF5YXHFqfSH2Dkhonm/<0;1>/*,[8dfecfc3/48h/0h/0h/2h]xpub661MyMwAqRbcFFr2SGY3dUn7g8P9VKNZdKWL2Z2pZMEkBWH2D1KTcwTn7keZQCaScCx7BUDjHFJJHnzBvDgUFgNjYsQTRvo7LWfYEtt78Pb/<0;1>/*))";
+        let desc = Descriptors::try_from_line(multisig).unwrap();
+        assert_eq!(desc.cosigner_count(), 3);
+    }
+
+    #[test]
+    fn test_fingerprints() {
+        let fingerprints = known_desc().fingerprints();
+        assert_eq!(fingerprints, vec![known_desc().fingerprint().unwrap()]);
+
+        let multisig = "wsh(sortedmulti(2,[817e7be0/48h/0h/0h/2h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>/*,[90645a28/48h/0h/0h/2h]xpub6CCKAvUTNursEnaJ8k1d27LfqEUzeAx2N9wFqYE3W1xh7nqgJEBEbLSSmohwDxzsSvcsYqiQqFzRvta65Njbe5o84bF5YXHFqfSH2Dkhonm/<0;1>/*))";
+        let desc = Descriptors::try_from_line(multisig).unwrap();
+
+        let fingerprints = desc
+            .fingerprints()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+
+        assert_eq!(fingerprints, vec!["817e7be0", "90645a28"]);
+    }
+
+    #[test]
+    fn test_json_descriptor() {
+        let json_descriptor = r##"{   "label": "test1",   "blockheight": 607985,   "descriptor": "wpkh([73c5da0a/84h/0h/0h]xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V/<0;1>/*)" }"##;
+        let desc = Descriptors::try_from(json_descriptor);
+        assert!(desc.is_ok());
+    }
+
+    #[test]
+    fn test_try_from_child_xpub() {
+        let zpub = "zpub6rNrPrFwgm4wMBSysetK5tpLBS2HYT8TDKQA6amxFHKJUnQq8rNtc4JDfGYPbvF9wJyagPpG1Faqnfe3BB8XzKon8LwW9KkMWyAQ4RQHzB1";
+        let desc = Descriptors::try_from_child_xpub(zpub, ScriptType::P2wpkh).unwrap();
+
+        assert_eq!(
+            desc.external.to_string().split('#').next().unwrap(),
+            "wpkh([00000000/84'/0'/0']xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/0/*)"
+        );
+
+        let address = desc
+            .address_at(Keychain::External, 0, Network::Bitcoin)
+            .unwrap();
+        assert_eq!(
+            address.to_string(),
+            "bc1q0g0vn4yqyk0zjwxw0zv5pltyyczty004zc9g7r"
+        );
+    }
+
+    #[test]
+    fn test_parse_testnet_descriptor() {
+        let tpub = "tpubDC8msFGeGuwnKG9Upg7DM2b4DaRqg3CUZa5g8v2SRQ6K4NSkxUgd7HsL2XVWbVm39yBA4LAxysQAm397zwQSQoQgewGiYZqrA9DsP4zbQ1M";
+        let desc = Descriptors::try_from_line(&format!("wpkh([73c5da0a/84h/1h/0h]{tpub}/<0;1>/*)"))
+            .unwrap();
+
+        assert_eq!(desc.xpub().unwrap().to_string(), tpub);
+        assert_eq!(
+            desc.address_at(Keychain::External, 0, Network::Testnet)
+                .unwrap()
+                .to_string(),
+            "tb1q6rz28mcfaxtmd6v789l9rrlrusdprr9pqcpvkl"
+        );
+    }
+
+    #[test]
+    fn test_try_from_child_xpub_with_keychains() {
+        let xpub = "xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM";
+        let desc =
+            Descriptors::try_from_child_xpub_with_keychains(xpub, ScriptType::P2pkh, 0, 2).unwrap();
+
+        assert!(desc.external.to_string().starts_with("pkh("));
+        assert!(desc.external.to_string().contains("/0/*"));
+        assert!(desc.internal.to_string().contains("/2/*"));
+
+        let same = Descriptors::try_from_child_xpub_with_keychains(xpub, ScriptType::P2pkh, 1, 1);
+        assert!(matches!(same, Err(Error::SameKeychains(1))));
+
+        let master = "xpub661MyMwAqRbcFFr2SGY3dUn7g8P9VKNZdKWL2Z2pZMEkBWH2D1KTcwTn7keZQCaScCx7BUDjHFJJHnzBvDgUFgNjYsQTRvo7LWfYEtt78Pb";
+        let master = Descriptors::try_from_child_xpub(master, ScriptType::P2wpkh);
+        assert!(matches!(master, Err(Error::MasterXpub)));
+    }
+
+    #[test]
+    fn test_is_synthesized() {
+        assert!(!known_desc().is_synthesized());
+
+        let xpub = "xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM";
+        let desc = Descriptors::try_from_child_xpub(xpub, ScriptType::P2wpkh).unwrap();
+        assert!(desc.is_synthesized());
+
+        let given = format!("{:#}", desc.external).replace("/0/*", "/<0;1>/*");
+        let given = Descriptors::try_from_line(&given).unwrap();
+        assert!(!given.is_synthesized());
+    }
+
+    #[test]
+    fn test_is_ranged() {
+        assert!(known_desc().is_ranged());
+
+        let fixed = "wpkh([817e7be0/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>)";
+        let desc = Descriptors::try_from_line(fixed).unwrap();
+        assert!(!desc.is_ranged());
+    }
+
+    #[test]
+    fn test_try_from_key_expression() {
+        let key = "[817e7be0/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>/*";
+        let key = KeyExpression::try_from_str(key).unwrap();
+
+        let desc = Descriptors::try_from_key_expression(&key).unwrap();
+        assert_eq!(desc, known_desc());
+        assert!(!desc.is_synthesized());
+
+        let no_multipath = "[817e7be0/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM";
+        let key = KeyExpression::try_from_str(no_multipath).unwrap();
+        assert_eq!(
+            Descriptors::try_from_key_expression(&key).unwrap(),
+            known_desc()
+        );
+    }
+
+    #[test]
+    fn test_multipath_key() {
+        let xpub = "xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM";
+
+        assert_eq!(
+            multipath_key("817e7be0", "84h/0h/0h", xpub),
+            format!("[817e7be0/84h/0h/0h]{xpub}/<0;1>/*")
+        );
+
+        // an empty origin path has no trailing `/`
+        let keys = [
+            multipath_key("817e7be0", DerivationPath::master(), xpub),
+            multipath_key("817e7be0", "", xpub),
+            multipath_key("817e7be0", "m", xpub),
+        ];
+        for key in keys {
+            assert_eq!(key, format!("[817e7be0]{xpub}/<0;1>/*"));
+            assert!(DescriptorPublicKey::from_str(&key).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_hardened_marker_output() {
+        let desc = known_desc();
+
+        // the line known_desc is parsed from, checksum included
+        let line = desc.to_multipath_line_with_marker(HardenedMarker::default());
+        assert_eq!(line.unwrap(), "wpkh([817e7be0/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>/*)#60tjs4c7");
+
+        let (external, internal) = desc.to_separate_lines_with_marker(HardenedMarker::H);
+        assert!(external.ends_with("/0/*)#sqx4cjta"));
+        assert!(internal.ends_with("/1/*)#p5r598m9"));
+        assert!(!external.contains('\''));
+
+        let apostrophe = desc.to_multipath_line_with_marker(HardenedMarker::Apostrophe);
+        assert_eq!(apostrophe.unwrap(), desc.to_multipath_line().unwrap());
+
+        // built from the key expression or the JSON fields, the output is the same
+        let key = format!("[817e7be0/84'/0'/0']{}", desc.xpub().unwrap());
+        let from_key =
+            Descriptors::try_from_key_expression(&KeyExpression::try_from_str(&key).unwrap())
+                .unwrap();
+        let single_sig = SingleSig {
+            name: Some(ScriptType::P2wpkh),
+            xfp: None,
+            deriv: Some("m/84h/0h/0h".to_string()),
+            xpub: Some(desc.xpub().unwrap().to_string()),
+            descriptor: None,
+            first: None,
+        };
+        let from_json = Descriptors::try_from_single_sig(single_sig, Some("817E7BE0")).unwrap();
+
+        assert_eq!(
+            from_key.to_separate_lines_with_marker(HardenedMarker::H),
+            from_json.to_separate_lines_with_marker(HardenedMarker::H)
+        );
+    }
+
+    #[test]
+    fn test_canonical_string() {
+        let canonical = known_desc().canonical_string();
+        assert!(canonical.starts_with("wpkh([817e7be0/84'/0'/0']xpub6CiK"));
+        assert!(canonical.contains("/<0;1>/*)#"));
+
+        // the same wallet written differently
+        let written = "wpkh([817e7be0/m/84'/0'/0']xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>/*)";
+        let desc = Descriptors::try_from_line(written).unwrap();
+        assert_eq!(desc.canonical_string(), canonical);
+
+        assert_eq!(
+            Descriptors::try_from_line(&canonical).unwrap(),
+            known_desc()
+        );
+    }
+
+    #[test]
+    fn test_wasabi_account_key_path() {
+        let json = r#"{
+            "ColdCardFirmwareVersion": "5.4.0",
+            "MasterFingerprint": "817E7BE0",
+            "ExtPubKey": "xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM",
+            "AccountKeyPath": "m/84'/0'/1'"
+        }"#;
+
+        let json = serde_json::from_str::<WasabiJson>(json).unwrap();
+        let desc = Descriptors::try_from(json).unwrap();
+        assert!(desc
+            .external
+            .to_string()
+            .starts_with("wpkh([817e7be0/84'/0'/1']"));
+    }
+
+    #[test]
+    fn test_multipath_and_separate_lines() {
+        let desc = known_desc();
+
+        let multipath = desc.to_multipath_line().unwrap();
+        assert!(multipath.contains("/<0;1>/*)#"));
+        assert_eq!(Descriptors::try_from_line(&multipath).unwrap(), desc);
+
+        let (external, internal) = desc.to_separate_lines();
+        assert!(external.contains("/0/*)#"));
+        assert!(internal.contains("/1/*)#"));
+        assert_eq!(
+            Descriptors::try_from(format!("{external}\n{internal}").as_str()).unwrap(),
+            desc
+        );
+
+        let mismatched = Descriptors {
+            internal: Descriptors::try_from_line("wpkh([90645a28/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>/*)")
+                .unwrap()
+                .internal,
+            ..desc
+        };
+        assert!(matches!(
+            mismatched.to_multipath_line(),
+            Err(Error::KeychainsNotMultipath)
+        ));
+    }
+
+    #[test]
+    fn test_validate_uniform_network() {
+        assert_eq!(
+            known_desc().validate_uniform_network().unwrap(),
+            Network::Bitcoin
+        );
+
+        let xpub = "xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM";
+        let tpub = "tpubDFH9dgzveyD8zTbPUFuLrGmCydNvxehyNdUXKJAQN8x4aZ4j6UZqGfnqFrD4NqyaTVGKbvEW54tsvPTK2UoSbCC1PJY8iCNiwTL3RWZEheQ";
+        let testnet =
+            Descriptors::try_from_line(&format!("wpkh([73c5da0a/84h/1h/0h]{tpub}/<0;1>/*)"))
+                .unwrap();
+        assert_eq!(
+            testnet.validate_uniform_network().unwrap(),
+            Network::Testnet
+        );
+
+        let mixed = Descriptors::try_from_line(&format!(
+            "wsh(sortedmulti(1,[817e7be0/48h/0h/0h/2h]{xpub}/<0;1>/*,[73c5da0a/48h/1h/0h/2h]{tpub}/<0;1>/*))"
+        ))
+        .unwrap();
+        match mixed.validate_uniform_network() {
+            Err(Error::MixedNetworks { networks }) => {
+                assert_eq!(networks, [Network::Bitcoin, Network::Testnet])
+            }
+            other => panic!("expected mixed networks, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_network_prefixed_descriptor() {
+        let desc = known_desc();
+        let line = desc.network_prefixed_descriptor(Network::Bitcoin).unwrap();
+        assert_eq!(line, desc.to_multipath_line().unwrap());
+        assert!(line.contains("]xpub"));
+
+        assert!(matches!(
+            desc.network_prefixed_descriptor(Network::Testnet),
+            Err(Error::NetworkKeyMismatch {
+                network: Network::Testnet,
+                key_network: NetworkKind::Main
+            })
+        ));
+
+        let tpub = "tpubDC8msFGeGuwnKG9Upg7DM2b4DaRqg3CUZa5g8v2SRQ6K4NSkxUgd7HsL2XVWbVm39yBA4LAxysQAm397zwQSQoQgewGiYZqrA9DsP4zbQ1M";
+        let testnet =
+            Descriptors::try_from_line(&format!("wpkh([73c5da0a/84h/1h/0h]{tpub}/<0;1>/*)"))
+                .unwrap();
+        for network in [Network::Testnet, Network::Signet, Network::Regtest] {
+            let line = testnet.network_prefixed_descriptor(network).unwrap();
+            assert!(line.contains("]tpub"));
+        }
+        assert!(testnet
+            .network_prefixed_descriptor(Network::Bitcoin)
+            .is_err());
+    }
+
+    #[test]
+    fn test_source_fingerprint_string() {
+        let string = std::fs::read_to_string("test/data/new-wasabi.json").unwrap();
+        let json = serde_json::from_str::<WasabiJson>(&string).unwrap();
+        let source = json.master_fingerprint.clone();
+        let desc = Descriptors::try_from(json).unwrap();
+        assert_eq!(desc.source_fingerprint_string().unwrap(), source);
+        assert_eq!(desc.fingerprints()[0].to_string(), source.to_lowercase());
+
+        let line = known_desc()
+            .to_multipath_line()
+            .unwrap()
+            .replace("[817e7be0", "[817E7BE0");
+        let (line, _) = line.split_once('#').unwrap();
+        let desc = Descriptors::try_from_line(line).unwrap();
+        assert_eq!(desc.source_fingerprint_string().unwrap(), "817E7BE0");
+        assert_eq!(desc, known_desc());
+
+        assert_eq!(
+            known_desc().source_fingerprint_string().unwrap(),
+            "817e7be0"
+        );
+
+        // electrum only has the integer form
+        let string = std::fs::read_to_string("test/data/new-electrum.json").unwrap();
+        let json = serde_json::from_str::<ElectrumJson>(&string).unwrap();
+        let (desc, _) = Descriptors::try_from_electrum(json, true).unwrap();
+        assert_eq!(desc.source_fingerprint_string().unwrap(), "817e7be0");
+    }
+
+    #[test]
+    fn test_json_string_array() {
+        let line = "wpkh([817e7be0/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>/*)#60tjs4c7";
+
+        let array = serde_json::json!([line]).to_string();
+        assert_eq!(Descriptors::try_from(array.as_str()).unwrap(), known_desc());
+
+        let desc = known_desc();
+        let (external, internal) = desc.to_separate_lines();
+        let array = serde_json::to_string_pretty(&[external, internal]).unwrap();
+        assert_eq!(Descriptors::try_from(array.as_str()).unwrap(), desc);
+
+        assert!(matches!(
+            Descriptors::try_from("[]"),
+            Err(Error::MissingDescriptor)
+        ));
+    }
+
+    #[test]
+    fn test_to_multipath_string() {
+        let desc = known_desc();
+        let line = desc.to_multipath_string().unwrap();
+        assert_eq!(line, desc.to_multipath_line().unwrap());
+        assert!(line.contains("/<0;1>/*)#"));
+        assert!(verify_checksum(&line).is_ok());
+
+        let xpub = "xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM";
+        for (receive, change) in [(2, 3), (0, 2), (1, 0)] {
+            let desc = Descriptors::try_from_child_xpub_with_keychains(
+                xpub,
+                ScriptType::P2wpkh,
+                receive,
+                change,
+            )
+            .unwrap();
+
+            assert!(desc.to_multipath_line().is_ok());
+            match desc.to_multipath_string() {
+                Err(Error::NonContiguousMultipath {
+                    receive: r,
+                    change: c,
+                }) => {
+                    assert_eq!((r, c), (receive.to_string(), change.to_string()))
+                }
+                other => panic!("expected non contiguous keychains, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_origin_path_string() {
+        let desc = known_desc();
+
+        assert_eq!(
+            desc.origin_path_string(PathFormat::MASTER_H).unwrap(),
+            "m/84h/0h/0h"
+        );
+        assert_eq!(
+            desc.origin_path_string(PathFormat {
+                marker: crate::key_expression::HardenedMarker::Apostrophe,
+                master_prefix: false,
+            })
+            .unwrap(),
+            "84'/0'/0'"
+        );
+
+        let record = std::fs::read_to_string("test/data/bsms-descriptor-record.txt").unwrap();
+        let multisig = crate::bsms::Bsms::try_from_str(&record).unwrap();
+        let multisig = multisig.to_descriptors().unwrap();
+        assert!(multisig.origin_path_string(PathFormat::MASTER_H).is_none());
+    }
+
+    #[test]
+    fn test_with_checksum() {
+        let json = std::fs::read_to_string("test/data/sparrow-export.json").unwrap();
+        let json = serde_json::from_str::<crate::json::GenericJson>(&json).unwrap();
+        let mut single_sig = json.bip84.unwrap();
+        single_sig.descriptor = None;
+
+        let desc = Descriptors::try_from_single_sig(single_sig, Some("817e7be0")).unwrap();
+        assert!(desc.original_string(Keychain::External).is_none());
+
+        let external = desc.external_with_checksum();
+        let internal = desc.internal_with_checksum();
+        assert_eq!(external, desc.external.to_string());
+        assert!(external.contains("/0/*)#"));
+        assert!(verify_checksum(&external).is_ok());
+        assert!(verify_checksum(&internal).is_ok());
+
+        let (body, _) = external.split_once('#').unwrap();
+        assert!(matches!(verify_checksum(body), Err(Error::MissingChecksum)));
+        assert!(matches!(
+            verify_checksum(&format!("{body}#{}", internal.split_once('#').unwrap().1)),
+            Err(Error::InvalidChecksum { .. })
+        ));
+    }
+
+    #[test]
+    fn test_trailing_comment() {
+        let line = "wpkh([817e7be0/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>/*)";
+
+        let commented = [
+            format!("{line} # label text"),
+            format!("{line}#label: cold wallet"),
+            format!("{line}#60tjs4c7 # label text"),
+            format!("{line} #"),
+            format!("{line} # hardware wallet"),
+            format!("{line} # Treasury"),
+            format!("{line}# 60tjs4c7"),
+        ];
+
+        for commented in commented {
+            assert_eq!(
+                Descriptors::try_from_line(&commented).unwrap(),
+                known_desc(),
+                "{commented}"
+            );
+        }
+
+        // checksum characters right after the `#` are checked, even if they only have letters
+        // or are cut off
+        for checksum in ["60tjs4c8", "hardware", "60tjs4c"] {
+            let desc = Descriptors::try_from_line(&format!("{line}#{checksum}"));
+            assert!(
+                matches!(desc, Err(Error::InvalidDescriptorParse(_))),
+                "{checksum}: {desc:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_key() {
+        let xpub = "xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM";
+        let key = |suffix: &str| {
+            DescriptorPublicKey::from_str(&format!("[817e7be0/84'/0'/0']{xpub}{suffix}")).unwrap()
+        };
+
+        let desc = Descriptors::from_key(key("/<0;1>/*"), ScriptType::P2wpkh).unwrap();
+        assert_eq!(desc, known_desc());
+
+        let desc = Descriptors::from_key(key("/0/*"), ScriptType::P2wpkh).unwrap();
+        assert_eq!(desc, known_desc());
+
+        let desc = Descriptors::from_key(key("/<0;1>/*"), ScriptType::P2shP2wpkh).unwrap();
+        assert_eq!(desc.script_type(), Some(ScriptType::P2shP2wpkh));
+
+        assert!(matches!(
+            Descriptors::from_key(key("/0/5"), ScriptType::P2wpkh),
+            Err(Error::NotRangedKey(_))
+        ));
+        assert!(matches!(
+            Descriptors::from_key(key("/1/*"), ScriptType::P2wpkh),
+            Err(Error::NotReceiveKeychain(_))
+        ));
+        assert!(matches!(
+            Descriptors::from_key(key("/<0;1;2>/*"), ScriptType::P2wpkh),
+            Err(Error::TooManyKeys(3))
+        ));
+    }
+
+    #[test]
+    fn test_musig_not_supported() {
+        let xpub = "xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM";
+        let other = "xpub6CCKAvUTNursEnaJ8k1d27LfqEUzeAx2N9wFqYE3W1xh7nqgJEBEbLSSmohwDxzsSvcsYqiQqFzRvta65Njbe5o84bF5YXHFqfSH2Dkhonm";
+        let line = format!("tr(musig({xpub},{other})/<0;1>/*)");
+
+        assert!(matches!(
+            Descriptors::try_from_line(&line),
+            Err(Error::MuSigNotSupported)
+        ));
+
+        let error = crate::Format::try_new_from_str(&line).unwrap_err();
+        assert!(error.root_cause_message().contains("MuSig2"));
+    }
+
+    #[test]
+    fn test_private_key_not_allowed() {
+        let xprv = "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi";
+
+        let line = format!("wpkh([817e7be0/84h/0h/0h]{xprv}/<0;1>/*)");
+        assert!(matches!(
+            Descriptors::try_from_line(&line),
+            Err(Error::PrivateKeyNotAllowed)
+        ));
+
+        let lines = format!("wpkh({xprv}/0/*)\nwpkh({xprv}/1/*)");
+        assert!(matches!(
+            Descriptors::try_from(lines.as_str()),
+            Err(Error::PrivateKeyNotAllowed)
+        ));
+
+        // a WIF key is swapped for its public key by miniscript
+        let wif = "wpkh(KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU73sVHnoWn)";
+        assert!(matches!(
+            Descriptors::try_from_line(wif),
+            Err(Error::PrivateKeyNotAllowed)
+        ));
+
+        let json = serde_json::json!({
+            "xfp": "817E7BE0",
+            "bip84": { "desc": line },
+        });
+        let json = serde_json::from_value::<crate::json::GenericJson>(json).unwrap();
+        assert!(matches!(
+            crate::formats::Json::try_from(json),
+            Err(crate::Error::InvalidDescriptor(Error::PrivateKeyNotAllowed))
+        ));
+    }
+
+    #[test]
+    fn test_xpub_in_original_format() {
+        let zpub = "zpub6rNrPrFwgm4wMBSysetK5tpLBS2HYT8TDKQA6amxFHKJUnQq8rNtc4JDfGYPbvF9wJyagPpG1Faqnfe3BB8XzKon8LwW9KkMWyAQ4RQHzB1";
+        let string = std::fs::read_to_string("test/data/new-electrum.json").unwrap();
+        let json = serde_json::from_str::<ElectrumJson>(&string).unwrap();
+        let (desc, _) = Descriptors::try_from_electrum(json, true).unwrap();
+        assert_eq!(desc.xpub_in_original_format().unwrap(), zpub);
+
+        // kept when the descriptors are stored and loaded again
+        let stored = serde_json::to_string(&desc).unwrap();
+        let loaded = serde_json::from_str::<Descriptors>(&stored).unwrap();
+        assert_eq!(loaded.xpub_in_original_format().unwrap(), zpub);
+
+        let ypub = "ypub6X2aUb9NXbQM65mQy6oFECSB1CdSanwXHGTUcw7vt2LaAteuYtLoDQ6ao1fXDsenrZjgJKJyHvLypBBeo59cSKUivvwW8S6k7PVvQkVosxZ";
+        let desc = Descriptors::try_from_xpub_str(ypub, ScriptType::P2shP2wpkh).unwrap();
+        assert_eq!(desc.xpub_in_original_format().unwrap(), ypub);
+
+        // an xpub stays an xpub even for a native segwit wallet
+        let xpub = "xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM";
+        let desc = Descriptors::try_from_child_xpub(xpub, ScriptType::P2wpkh).unwrap();
+        assert_eq!(desc.xpub_in_original_format().unwrap(), xpub);
+        assert_eq!(known_desc().xpub_in_original_format().unwrap(), xpub);
+
+        let key = KeyExpression::try_from_str(&format!("[817e7be0/84h/0h/0h]{zpub}")).unwrap();
+        let desc = Descriptors::try_from_key_expression(&key).unwrap();
+        assert_eq!(desc.xpub_in_original_format().unwrap(), zpub);
+    }
+
+    #[test]
+    fn test_to_electrum_json() {
+        let string = std::fs::read_to_string("test/data/new-electrum.json").unwrap();
+        let fixture = serde_json::from_str::<ElectrumJson>(&string).unwrap();
+        let (desc, _) = Descriptors::try_from_electrum(fixture.clone(), true).unwrap();
+
+        let json = desc.to_electrum_json(Network::Bitcoin).unwrap();
+        assert_eq!(json.seed_version, 17);
+        assert_eq!(json.wallet_type, "standard");
+        assert_eq!(json.keystore.derivation, fixture.keystore.derivation);
+        assert_eq!(json.keystore.xpub, fixture.keystore.xpub);
+        assert_eq!(json.keystore.ckcc_xfp, Some(3766189697));
+
+        let exported = serde_json::to_string(&json).unwrap();
+        let reimported = serde_json::from_str::<ElectrumJson>(&exported).unwrap();
+        let (reimported, warning) = Descriptors::try_from_electrum(reimported, true).unwrap();
+        assert_eq!(reimported, desc);
+        assert!(warning.is_none());
+
+        assert!(matches!(
+            desc.to_electrum_json(Network::Testnet),
+            Err(Error::NetworkKeyMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_address_at() {
+        let desc = known_desc();
+        let address = |keychain, index| {
+            desc.address_at(keychain, index, Network::Bitcoin)
+                .unwrap()
+                .to_string()
+        };
+
+        assert_eq!(
+            address(Keychain::External, 0),
+            "bc1q0g0vn4yqyk0zjwxw0zv5pltyyczty004zc9g7r"
+        );
+        assert_eq!(
+            address(Keychain::External, 1),
+            "bc1q9c7y05thzt29z5lgpccgknuvx2afzqljawuuay"
+        );
+        assert_eq!(
+            address(Keychain::Internal, 0),
+            "bc1q6fvfndpnl34ndmmygelmwfxzg9n59pjulfr8my"
+        );
+    }
+
+    #[test]
+    fn test_address_index_out_of_range() {
+        let desc = known_desc();
+
+        assert!(desc
+            .address_at(Keychain::External, MAX_DERIVATION_INDEX, Network::Bitcoin)
+            .is_ok());
+
+        for index in [MAX_DERIVATION_INDEX + 1, u32::MAX] {
+            assert!(matches!(
+                desc.address_at(Keychain::Internal, index, Network::Bitcoin),
+                Err(Error::IndexOutOfRange(i)) if i == index
+            ));
+            assert!(matches!(
+                desc.verify_message(index, "message", "c2lnbmF0dXJl"),
+                Err(Error::IndexOutOfRange(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_taproot_single_sig() {
+        let xpub = "xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ";
+        let key = KeyExpression::try_from_str(&format!("[73c5da0a/86h/0h/0h]{xpub}")).unwrap();
+        let desc = Descriptors::try_from_key_expression(&key).unwrap();
 
-    Ok(descriptor)
-}
+        assert_eq!(
+            desc,
+            Descriptors::try_from_line(&format!("tr([73c5da0a/86h/0h/0h]{xpub}/<0;1>/*)")).unwrap()
+        );
+        assert_eq!(desc.script_type(), Some(ScriptType::P2tr));
+        assert_eq!(desc.fingerprint().unwrap().to_string(), "73c5da0a");
+        assert_eq!(desc.xpub().unwrap().to_string(), xpub);
+        assert_eq!(
+            desc.address_at(Keychain::External, 0, Network::Bitcoin)
+                .unwrap()
+                .to_string(),
+            "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"
+        );
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        // the unspendable internal key of a script path wallet is not one of its keys
+        for script in [
+            format!("pk([73c5da0a/86h/0h/0h]{xpub}/<0;1>/*)"),
+            format!("multi_a(1,[73c5da0a/86h/0h/0h]{xpub}/<0;1>/*)"),
+        ] {
+            let line = format!("tr({},{script})", crate::multisig::NUMS_INTERNAL_KEY);
+            let desc = Descriptors::try_from_line(&line).unwrap();
+            assert_eq!(desc.fingerprint().unwrap().to_string(), "73c5da0a");
+            assert_eq!(desc.xpub().unwrap().to_string(), xpub);
+            assert_eq!(desc.cosigner_count(), 1);
+        }
+    }
 
-    fn known_desc() -> Descriptors {
-        let known_desc = "wpkh([817e7be0/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>/*)#60tjs4c7";
-        Descriptors::try_from_line(known_desc).unwrap()
+    #[test]
+    fn test_timelocked_single_key() {
+        let xpub = "xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM";
+        let line = format!("wsh(and_v(v:pk([817e7be0/84h/0h/0h]{xpub}/<0;1>/*),older(144)))");
+        let desc = Descriptors::try_from_line(&line).unwrap();
+
+        assert_eq!(desc.fingerprint().unwrap().to_string(), "817e7be0");
+        assert_eq!(desc.xpub().unwrap().to_string(), xpub);
+
+        let other = "xpub6BosfCnifzxcFwrSzQiqu2DBVTshkCXacvNsWGYJVVhhawA7d4R5WSWGFNbi8Aw6ZRc1brxMyWMzG3DSSSSoekkudhUd9yLb6qx39T9nMdj";
+        let line =
+            format!("wsh(or_d(pk({xpub}/<0;1>/*),and_v(v:pk({other}/<0;1>/*),after(800000))))");
+        let desc = Descriptors::try_from_line(&line).unwrap();
+
+        assert_eq!(desc.fingerprint(), None);
+        assert!(matches!(desc.xpub(), Err(Error::MultipleKeys(2))));
     }
 
     #[test]
-    fn test_parse_combination_descriptor() {
+    fn test_fresh_checksums_after_fingerprint() {
+        let placeholder = "wpkh([00000000/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>/*)";
+        let placeholder = Descriptors::try_from_line(placeholder).unwrap();
+
+        let fingerprint = Fingerprint::from_str("817e7be0").unwrap();
+        let desc = placeholder.with_master_fingerprint(fingerprint).unwrap();
+
+        let (external, internal) = desc.to_separate_lines();
         let secp = &secp256k1::Secp256k1::signing_only();
-        let descriptor = "wpkh([817e7be0/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>/*)#60tjs4c7";
-        let desc = Descriptors::try_from_line(descriptor);
+        for line in [&external, &internal] {
+            let (_, checksum) = line.split_once('#').unwrap();
+            assert_eq!(checksum.len(), 8);
+            assert!(Descriptor::parse_descriptor(secp, line).is_ok(), "{line}");
+        }
 
-        assert!(desc.is_ok());
-        let desc = desc.unwrap();
+        assert_eq!(external, known_desc().external.to_string());
+        assert_eq!(internal, known_desc().internal.to_string());
+    }
 
-        let (external, _) = Descriptor::parse_descriptor(secp, "wpkh([817e7be0/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/0/*)#sqx4cjta").unwrap();
-        let (internal, _) = Descriptor::parse_descriptor(secp, "wpkh([817e7be0/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/1/*)#p5r598m9").unwrap();
+    #[test]
+    fn test_verify_master_fingerprint() {
+        let master = xpub::Xpub::try_from("xpub661MyMwAqRbcFFr2SGY3dUn7g8P9VKNZdKWL2Z2pZMEkBWH2D1KTcwTn7keZQCaScCx7BUDjHFJJHnzBvDgUFgNjYsQTRvo7LWfYEtt78Pb").unwrap();
+        let account = xpub::Xpub::try_from("xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM").unwrap();
+
+        let right = Fingerprint::from_str("817e7be0").unwrap();
+        let wrong = Fingerprint::from_str("90645a28").unwrap();
+
+        assert!(verify_master_fingerprint(right, &master).is_ok());
+        assert!(matches!(
+            verify_master_fingerprint(wrong, &master),
+            Err(Error::FingerprintXpubMismatch { stated, computed }) if stated == wrong && computed == right
+        ));
+
+        // an account xpub doesn't know its master fingerprint, right or wrong
+        for stated in [right, wrong] {
+            assert!(matches!(
+                verify_master_fingerprint(stated, &account),
+                Err(Error::FingerprintUnverifiable(3))
+            ));
+        }
+    }
 
-        assert_eq!(desc.external, external);
-        assert_eq!(desc.internal, internal);
+    #[test]
+    fn test_electrum_verified() {
+        let string = std::fs::read_to_string("test/data/new-electrum.json").unwrap();
+        let json = serde_json::from_str::<ElectrumJson>(&string).unwrap();
+        assert!(Descriptors::try_from_electrum_verified(json, true).is_ok());
+
+        // 0x90645a28 read little endian
+        let string = string.replace("3766189697", "677012624");
+        let json = serde_json::from_str::<ElectrumJson>(&string).unwrap();
+        assert!(matches!(
+            Descriptors::try_from_electrum_verified(json, true),
+            Err(Error::FingerprintXpubMismatch { .. })
+        ));
     }
 
     #[test]
-    fn test_fingerprint_getter() {
-        let single_sig = r#"{
-    "name": "p2wpkh",
-    "xfp": "8DFECFC3",
-    "deriv": "m/84h/0h/0h",
-    "xpub": "xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM",
-    "desc": "wpkh([817e7be0/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>/*)#60tjs4c7",
-    "_pub": "zpub6rNrPrFwgm4wMBSysetK5tpLBS2HYT8TDKQA6amxFHKJUnQq8rNtc4JDfGYPbvF9wJyagPpG1Faqnfe3BB8XzKon8LwW9KkMWyAQ4RQHzB1",
-    "first": "bc1q0g0vn4yqyk0zjwxw0zv5pltyyczty004zc9g7r"
-        }"#;
+    fn test_single_sig_verified_first_address() {
+        let single_sig = SingleSig {
+            name: Some(ScriptType::P2wpkh),
+            deriv: Some("m/84h/0h/0h".to_string()),
+            xpub: Some("xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM".to_string()),
+            first: Some("bc1q0g0vn4yqyk0zjwxw0zv5pltyyczty004zc9g7r".to_string()),
+            ..Default::default()
+        };
 
-        let single_sig: SingleSig = serde_json::from_str(single_sig).unwrap();
-        let parse_desc = Descriptors::try_from_single_sig(single_sig, None).unwrap();
+        let desc =
+            Descriptors::try_from_single_sig_first_verified(single_sig.clone(), Some("817e7be0"));
+        assert_eq!(desc.unwrap(), known_desc());
 
-        assert_eq!(
-            parse_desc
-                .fingerprint()
-                .unwrap()
-                .to_string()
-                .to_uppercase()
-                .as_str(),
-            "817E7BE0"
-        );
+        // an account xpub can't confirm the fingerprint, a wrong one isn't let through
+        for fingerprint in ["817e7be0", "90645a28"] {
+            assert!(matches!(
+                Descriptors::try_from_single_sig_verified(single_sig.clone(), Some(fingerprint)),
+                Err(Error::FingerprintUnverifiable(3))
+            ));
+        }
+
+        // the second receive address
+        let wrong_first = SingleSig {
+            first: Some("bc1q9c7y05thzt29z5lgpccgknuvx2afzqljawuuay".to_string()),
+            ..single_sig.clone()
+        };
+        assert!(matches!(
+            Descriptors::try_from_single_sig_first_verified(wrong_first.clone(), Some("817e7be0")),
+            Err(Error::FirstAddressMismatch { got, .. }) if got == "bc1q0g0vn4yqyk0zjwxw0zv5pltyyczty004zc9g7r"
+        ));
+
+        // the unverified path ignores `first`
+        assert!(Descriptors::try_from_single_sig(wrong_first, Some("817e7be0")).is_ok());
+
+        let invalid = SingleSig {
+            first: Some("not an address".to_string()),
+            ..single_sig
+        };
+        assert!(matches!(
+            Descriptors::try_from_single_sig_first_verified(invalid, Some("817e7be0")),
+            Err(Error::InvalidAddress(_))
+        ));
     }
 
     #[test]
-    fn test_parse_without_descriptor() {
-        let single_sig = r#"{
-    "name": "p2wpkh",
-    "xfp": "8DFECFC3",
-    "deriv": "m/84h/0h/0h",
-    "xpub": "xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM",
-    "_pub": "zpub6rNrPrFwgm4wMBSysetK5tpLBS2HYT8TDKQA6amxFHKJUnQq8rNtc4JDfGYPbvF9wJyagPpG1Faqnfe3BB8XzKon8LwW9KkMWyAQ4RQHzB1",
-    "first": "bc1q0g0vn4yqyk0zjwxw0zv5pltyyczty004zc9g7r"
-        }"#;
+    fn test_hardened_marker_is_the_same_for_every_entry_point() {
+        let xpub = "xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM";
+        let fingerprint = Fingerprint::from_str("817e7be0").unwrap();
+
+        let single_sig = SingleSig {
+            name: Some(ScriptType::P2wpkh),
+            deriv: Some("m/84h/0h/0h".to_string()),
+            xpub: Some(xpub.to_string()),
+            ..Default::default()
+        };
 
-        let single_sig: SingleSig = serde_json::from_str(single_sig).unwrap();
+        let wasabi = WasabiJson {
+            cold_card_firmware_version: "5.4.0".to_string(),
+            master_fingerprint: "817E7BE0".to_string(),
+            ext_pub_key: xpub.to_string(),
+            account_key_path: Some("m/84'/0'/0'".to_string()),
+        };
 
-        let parse_desc = Descriptors::try_from_single_sig(single_sig, Some("817E7BE0"));
+        let key = KeyExpression::try_from_str(&format!("[817e7be0/84'/0'/0']{xpub}")).unwrap();
+
+        let entry_points = [
+            Descriptors::try_from_line(&format!("wpkh([817e7be0/84h/0h/0h]{xpub}/<0;1>/*)")),
+            Descriptors::try_from_line(&format!("wpkh([817e7be0/84'/0'/0']{xpub}/<0;1>/*)")),
+            Descriptors::try_from_single_sig(single_sig, Some("817e7be0")),
+            Descriptors::try_from(wasabi),
+            Descriptors::try_from_key_expression(&key),
+            Descriptors::try_from_child_xpub(xpub, ScriptType::P2wpkh)
+                .and_then(|desc| desc.with_master_fingerprint(fingerprint)),
+        ];
+
+        for desc in entry_points {
+            let desc = desc.unwrap();
+            assert_eq!(desc.external.to_string(), known_desc().external.to_string());
+            assert_eq!(desc.internal.to_string(), known_desc().internal.to_string());
+            assert_eq!(desc.canonical_string(), known_desc().canonical_string());
+        }
 
-        assert!(parse_desc.is_ok());
-        let parse_desc = parse_desc.unwrap();
+        assert!(known_desc()
+            .canonical_string()
+            .contains("[817e7be0/84'/0'/0']"));
+    }
 
-        assert_eq!(known_desc().external, parse_desc.external);
-        assert_eq!(known_desc().internal, parse_desc.internal);
+    #[test]
+    fn test_try_from_xpub_str() {
+        let zpub = "zpub6rNrPrFwgm4wMBSysetK5tpLBS2HYT8TDKQA6amxFHKJUnQq8rNtc4JDfGYPbvF9wJyagPpG1Faqnfe3BB8XzKon8LwW9KkMWyAQ4RQHzB1";
 
+        let desc = Descriptors::try_from_xpub_str(zpub, ScriptType::P2wpkh).unwrap();
         assert_eq!(
-            parse_desc.external.to_string(),
-            known_desc().external.to_string()
+            desc,
+            Descriptors::try_from_child_xpub(zpub, ScriptType::P2wpkh).unwrap()
         );
 
-        assert_eq!(
-            parse_desc.internal.to_string(),
-            known_desc().internal.to_string()
-        );
+        let mismatch = Descriptors::try_from_xpub_str(zpub, ScriptType::P2pkh);
+        assert!(matches!(
+            mismatch,
+            Err(Error::ScriptTypeMismatch(Warning::ScriptTypeMismatch {
+                from_prefix: ScriptType::P2wpkh,
+                from_path: ScriptType::P2pkh
+            }))
+        ));
+
+        // a plain xpub can be any script type
+        let xpub = "xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM";
+        assert!(Descriptors::try_from_xpub_str(xpub, ScriptType::P2pkh).is_ok());
     }
 
     #[test]
-    fn test_parse_wasabi() {
-        let json = r#"{
-            "ColdCardFirmwareVersion": "5.4.0",
-            "MasterFingerprint": "817E7BE0",
-            "ExtPubKey": "xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM"
-        }"#;
+    fn test_electrum_script_type_mismatch() {
+        let string = std::fs::read_to_string("test/data/new-electrum.json").unwrap();
+        let string = string.replace("m/84h/0h/0h", "m/44h/0h/0h");
 
-        let json = serde_json::from_str::<WasabiJson>(json).unwrap();
-        let desc = Descriptors::try_from(json);
+        let json = serde_json::from_str::<ElectrumJson>(&string).unwrap();
+        let (desc, warning) = Descriptors::try_from_electrum(json.clone(), false).unwrap();
 
-        assert!(desc.is_ok());
-        let desc = desc.unwrap();
+        assert!(desc.external.to_string().starts_with("pkh("));
+        assert_eq!(
+            warning,
+            Some(Warning::ScriptTypeMismatch {
+                from_prefix: ScriptType::P2wpkh,
+                from_path: ScriptType::P2pkh
+            })
+        );
 
-        assert_eq!(desc.external, known_desc().external);
-        assert_eq!(desc.internal, known_desc().internal);
+        let strict = Descriptors::try_from_electrum(json, true);
+        assert!(matches!(strict, Err(Error::ScriptTypeMismatch(w)) if Some(w) == warning));
     }
 
     #[test]
-    fn test_parse_electrum() {
-        let json = r#"{
-            "seed_version": 17,
-            "use_encryption": false,
-            "wallet_type": "standard",
-            "keystore": {
-                "type": "hardware",
-                "hw_type": "coldcard",
-                "label": "Coldcard Import 817E7BE0",
-                "ckcc_xfp": 3766189697,
-                "ckcc_xpub": "xpub661MyMwAqRbcFFr2SGY3dUn7g8P9VKNZdKWL2Z2pZMEkBWH2D1KTcwTn7keZQCaScCx7BUDjHFJJHnzBvDgUFgNjYsQTRvo7LWfYEtt78Pb",
-                "derivation": "m/84h/0h/0h",
-                "xpub": "zpub6rNrPrFwgm4wMBSysetK5tpLBS2HYT8TDKQA6amxFHKJUnQq8rNtc4JDfGYPbvF9wJyagPpG1Faqnfe3BB8XzKon8LwW9KkMWyAQ4RQHzB1"
-            }
-        }"#;
+    fn test_as_tuple() {
+        let desc = known_desc();
+        let (external, internal) = desc.as_tuple();
 
-        let electrum = serde_json::from_str::<ElectrumJson>(json);
-        assert!(electrum.is_ok());
+        assert_eq!(external, desc.descriptor(Keychain::External));
+        assert_eq!(internal, desc.descriptor(Keychain::Internal));
+        assert_ne!(external, internal);
 
-        let electrum = electrum.unwrap();
-        let desc = Descriptors::try_from(electrum);
+        let descriptors = Keychain::ALL.map(|keychain| desc.descriptor(keychain));
+        assert_eq!(descriptors, [external, internal]);
+    }
 
-        assert!(desc.is_ok());
-        let desc = desc.unwrap();
+    #[test]
+    fn test_account_fingerprint() {
+        let desc = known_desc();
 
-        assert_eq!(desc.external, known_desc().external);
-        assert_eq!(desc.internal, known_desc().internal);
+        // the bip84 account of the coldcard export has the xfp 8DFECFC3
+        assert_eq!(desc.account_fingerprint().unwrap().to_string(), "8dfecfc3");
+        assert_ne!(desc.account_fingerprint().ok(), desc.fingerprint());
     }
 
     #[test]
-    fn test_parse_electrum_without_xfp() {
-        let json = r#"{
-            "seed_version": 17,
-            "use_encryption": false,
-            "wallet_type": "standard",
-            "keystore": {
-                "type": "hardware",
-                "hw_type": "coldcard",
-                "label": "Coldcard Import 817E7BE0",
-                "ckcc_xpub": "xpub661MyMwAqRbcFFr2SGY3dUn7g8P9VKNZdKWL2Z2pZMEkBWH2D1KTcwTn7keZQCaScCx7BUDjHFJJHnzBvDgUFgNjYsQTRvo7LWfYEtt78Pb",
-                "derivation": "m/84h/0h/0h",
-                "xpub": "zpub6rNrPrFwgm4wMBSysetK5tpLBS2HYT8TDKQA6amxFHKJUnQq8rNtc4JDfGYPbvF9wJyagPpG1Faqnfe3BB8XzKon8LwW9KkMWyAQ4RQHzB1"
-            }
-        }"#;
+    fn test_with_master_fingerprint() {
+        let xpub = "xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM";
+        let desc = Descriptors::try_from_child_xpub(xpub, ScriptType::P2wpkh).unwrap();
+
+        let fingerprint = Fingerprint::from_str("817e7be0").unwrap();
+        let fixed = desc.with_master_fingerprint(fingerprint).unwrap();
+        assert_eq!(fixed, known_desc());
+        assert!(!fixed.is_synthesized());
+
+        // setting the same fingerprint again is fine, a different one is not
+        assert_eq!(fixed.with_master_fingerprint(fingerprint).unwrap(), fixed);
+
+        let other = Fingerprint::from_str("90645a28").unwrap();
+        assert!(matches!(
+            fixed.with_master_fingerprint(other),
+            Err(Error::FingerprintConflict { .. })
+        ));
+    }
 
-        let electrum = serde_json::from_str::<ElectrumJson>(json);
-        assert!(electrum.is_ok());
+    #[test]
+    fn test_standard_conformance() {
+        let xpub = "xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM";
+        let conformance = |line: String| {
+            Descriptors::try_from_line(&line)
+                .unwrap()
+                .standard_conformance()
+        };
 
-        let electrum = electrum.unwrap();
-        let desc = Descriptors::try_from(electrum);
+        assert_eq!(known_desc().standard_conformance(), Conformance::Standard);
+        assert_eq!(
+            conformance(format!("tr([817e7be0/86h/0h/0h]{xpub}/<0;1>/*)")),
+            Conformance::Standard
+        );
 
-        assert!(desc.is_ok());
-        let desc = desc.unwrap();
+        assert_eq!(
+            conformance(format!("wpkh([817e7be0/84h/0h/1h]{xpub}/<0;1>/*)")),
+            Conformance::NonStandard {
+                coin_type: None,
+                account: Some(1)
+            }
+        );
+        assert_eq!(
+            conformance(format!("wpkh([817e7be0/84h/1h/2h]{xpub}/<0;1>/*)")),
+            Conformance::NonStandard {
+                coin_type: Some(1),
+                account: Some(2)
+            }
+        );
 
-        assert_eq!(desc.external, known_desc().external);
-        assert_eq!(desc.internal, known_desc().internal);
+        // coin type 1 is the standard for testnet keys
+        let tpub = "tpubDC8msFGeGuwnKG9Upg7DM2b4DaRqg3CUZa5g8v2SRQ6K4NSkxUgd7HsL2XVWbVm39yBA4LAxysQAm397zwQSQoQgewGiYZqrA9DsP4zbQ1M";
+        assert_eq!(
+            conformance(format!("wpkh([73c5da0a/84h/1h/0h]{tpub}/<0;1>/*)")),
+            Conformance::Standard
+        );
+
+        let custom = [
+            format!("wpkh([817e7be0/44h/0h/0h]{xpub}/<0;1>/*)"),
+            format!("wpkh([817e7be0/84h/0h]{xpub}/<0;1>/*)"),
+            format!("wpkh([817e7be0/84h/0h/0]{xpub}/<0;1>/*)"),
+            format!("wpkh([817e7be0/84h/0h/0h/0h]{xpub}/<0;1>/*)"),
+        ];
+        for line in custom {
+            assert_eq!(conformance(line.clone()), Conformance::Custom, "{line}");
+        }
+
+        let other = "xpub6BosfCnifzxcFwrSzQiqu2DBVTshkCXacvNsWGYJVVhhawA7d4R5WSWGFNbi8Aw6ZRc1brxMyWMzG3DSSSSoekkudhUd9yLb6qx39T9nMdj";
+        assert_eq!(
+            conformance(format!("wpkh({xpub}/<0;1>/*)")),
+            Conformance::Unknown
+        );
+        assert_eq!(
+            conformance(format!(
+                "wsh(sortedmulti(1,[817e7be0/48h/0h/0h/2h]{xpub}/<0;1>/*,[73c5da0a/48h/0h/0h/2h]{other}/<0;1>/*))"
+            )),
+            Conformance::Unknown
+        );
     }
 
     #[test]
-    fn test_parse_electrum_without_ckcc() {
-        let json = r#"{
-            "seed_version": 17,
-            "use_encryption": false,
-            "wallet_type": "standard",
-            "keystore": {
-                "type": "hardware",
-                "hw_type": "coldcard",
-                "label": "Coldcard Import 817E7BE0",
-                "derivation": "m/84h/0h/0h",
-                "xpub": "zpub6rNrPrFwgm4wMBSysetK5tpLBS2HYT8TDKQA6amxFHKJUnQq8rNtc4JDfGYPbvF9wJyagPpG1Faqnfe3BB8XzKon8LwW9KkMWyAQ4RQHzB1"
-            }
-        }"#;
-
-        let electrum = serde_json::from_str::<ElectrumJson>(json);
-        assert!(electrum.is_ok());
+    fn test_slip132_xpub() {
+        assert_eq!(
+            known_desc().slip132_xpub().unwrap(),
+            "zpub6rNrPrFwgm4wMBSysetK5tpLBS2HYT8TDKQA6amxFHKJUnQq8rNtc4JDfGYPbvF9wJyagPpG1Faqnfe3BB8XzKon8LwW9KkMWyAQ4RQHzB1"
+        );
 
-        let electrum = electrum.unwrap();
-        let desc = Descriptors::try_from(electrum);
+        let xpub = "xpub6CCKAvUTNursEnaJ8k1d27LfqEUzeAx2N9wFqYE3W1xh7nqgJEBEbLSSmohwDxzsSvcsYqiQqFzRvta65Njbe5o84bF5YXHFqfSH2Dkhonm";
+        let desc =
+            Descriptors::try_from_line(&format!("sh(wpkh([817e7be0/49h/0h/0h]{xpub}/<0;1>/*))"))
+                .unwrap();
+        assert_eq!(
+            desc.slip132_xpub().unwrap(),
+            "ypub6X2aUb9NXbQM65mQy6oFECSB1CdSanwXHGTUcw7vt2LaAteuYtLoDQ6ao1fXDsenrZjgJKJyHvLypBBeo59cSKUivvwW8S6k7PVvQkVosxZ"
+        );
 
-        assert!(desc.is_ok());
-        let desc = desc.unwrap();
+        // no SLIP-132 prefix for taproot, the plain xpub is returned
+        let xpub = "xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ";
+        let desc =
+            Descriptors::try_from_line(&format!("tr([73c5da0a/86h/0h/0h]{xpub}/<0;1>/*)")).unwrap();
+        assert_eq!(desc.slip132_xpub().unwrap(), xpub);
 
-        let known_desc = "wpkh([90645a28/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>/*)#ujst24qf";
-        let known_desc = Descriptors::try_from_line(known_desc).unwrap();
+        let tpub = "tpubDC8msFGeGuwnKG9Upg7DM2b4DaRqg3CUZa5g8v2SRQ6K4NSkxUgd7HsL2XVWbVm39yBA4LAxysQAm397zwQSQoQgewGiYZqrA9DsP4zbQ1M";
+        let desc = Descriptors::try_from_line(&format!("wpkh([73c5da0a/84h/1h/0h]{tpub}/<0;1>/*)"))
+            .unwrap();
+        assert_eq!(
+            desc.slip132_xpub().unwrap(),
+            "vpub5Y6cjg78GGuNLsaPhmYsiw4gYX3HoQiRBiSwDaBXKUafCt9bNwWQiitDk5VZ5BVxYnQdwoTyXSs2JHRPAgjAvtbBrf8ZhDYe2jWAqvZVnsc"
+        );
 
-        assert_eq!(desc.external, known_desc.external);
-        assert_eq!(desc.internal, known_desc.internal);
+        let other = "xpub6BosfCnifzxcFwrSzQiqu2DBVTshkCXacvNsWGYJVVhhawA7d4R5WSWGFNbi8Aw6ZRc1brxMyWMzG3DSSSSoekkudhUd9yLb6qx39T9nMdj";
+        let multisig = Descriptors::try_from_line(&format!(
+            "wsh(sortedmulti(1,{xpub}/<0;1>/*,{other}/<0;1>/*))"
+        ))
+        .unwrap();
+        assert!(matches!(
+            multisig.slip132_xpub(),
+            Err(Error::NoXpubInDescriptor)
+        ));
     }
 
     #[test]
-    fn test_from_descriptors_file() {
-        let desc = r#"
-            wpkh([817e7be0/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/0/*)#sqx4cjta
-            wpkh([817e7be0/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/1/*)#p5r598m9
-        "#;
+    fn test_try_from_external_only() {
+        let xpub = "xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM";
+        let line = format!("wpkh([817e7be0/84h/0h/0h]{xpub}/0/*)");
+        let desc = Descriptors::try_from_external_only(&line).unwrap();
 
-        let desc = Descriptors::try_from(desc).unwrap();
+        assert_eq!(desc, known_desc());
+        assert_eq!(
+            desc.original_string(Keychain::External),
+            Some(line.as_str())
+        );
+        assert_eq!(desc.original_string(Keychain::Internal), None);
 
-        assert_eq!(desc.external.to_string(), known_desc().external.to_string());
-        assert_eq!(desc.internal.to_string(), known_desc().internal.to_string());
+        let other = "xpub6CCKAvUTNursEnaJ8k1d27LfqEUzeAx2N9wFqYE3W1xh7nqgJEBEbLSSmohwDxzsSvcsYqiQqFzRvta65Njbe5o84bF5YXHFqfSH2Dkhonm";
+        let multisig = Descriptors::try_from_external_only(&format!(
+            "wsh(sortedmulti(2,[817e7be0/48h/0h/0h/2h]{xpub}/0/*,[90645a28/48h/0h/0h/2h]{other}/0/*))"
+        ))
+        .unwrap();
+        assert_eq!(
+            multisig,
+            Descriptors::try_from_line(&format!(
+                "wsh(sortedmulti(2,[817e7be0/48h/0h/0h/2h]{xpub}/<0;1>/*,[90645a28/48h/0h/0h/2h]{other}/<0;1>/*))"
+            ))
+            .unwrap()
+        );
+
+        let not_receive = [
+            format!("wpkh([817e7be0/84h/0h/0h]{xpub}/1/*)"),
+            format!("wpkh([817e7be0/84h/0h/0h]{xpub}/0)"),
+            format!("wpkh([817e7be0/84h/0h/0h]{xpub}/<0;1>/*)"),
+            format!("wpkh([817e7be0/84h/0h/0h]{xpub}/*)"),
+        ];
+        for line in not_receive {
+            assert!(
+                matches!(
+                    Descriptors::try_from_external_only(&line),
+                    Err(Error::NotReceiveKeychain(_))
+                ),
+                "{line}"
+            );
+        }
     }
 
     #[test]
-    fn test_xpub_output() {
-        let know_desc = known_desc();
-        let xpub = know_desc.xpub();
+    fn test_script_type_and_origin() {
+        let desc = known_desc();
+        assert_eq!(desc.script_type(), Some(ScriptType::P2wpkh));
 
-        assert!(xpub.is_ok());
-        assert!(xpub.unwrap().to_string().starts_with("xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM"));
+        let (fingerprint, path) = desc.origin().unwrap();
+        assert_eq!(fingerprint.to_string(), "817e7be0");
+        assert_eq!(path, DerivationPath::from_str("m/84h/0h/0h").unwrap());
+
+        let nested = "sh(wpkh([817e7be0/49h/0h/0h]xpub6CCKAvUTNursEnaJ8k1d27LfqEUzeAx2N9wFqYE3W1xh7nqgJEBEbLSSmohwDxzsSvcsYqiQqFzRvta65Njbe5o84bF5YXHFqfSH2Dkhonm/<0;1>/*))";
+        let nested = Descriptors::try_from_line(nested).unwrap();
+        assert_eq!(nested.script_type(), Some(ScriptType::P2shP2wpkh));
+        assert_eq!(
+            nested.origin_derivation_path(),
+            Some(DerivationPath::from_str("m/49h/0h/0h").unwrap())
+        );
+        assert_eq!(desc.origin_derivation_path(), Some(path));
+
+        let record = std::fs::read_to_string("test/data/bsms-descriptor-record.txt").unwrap();
+        let multisig = crate::bsms::Bsms::try_from_str(&record)
+            .unwrap()
+            .to_descriptors()
+            .unwrap();
+        assert_eq!(multisig.script_type(), None);
+        assert_eq!(
+            multisig.origin_derivation_path(),
+            Some(DerivationPath::from_str("m/48h/0h/0h/2h").unwrap())
+        );
     }
 
     #[test]
-    fn test_get_master_fingerprint() {
-        let know_desc = known_desc();
-        let master_fingerprint = know_desc.fingerprint().unwrap();
-        assert_eq!(master_fingerprint.to_string().as_str(), "817e7be0");
+    fn test_pretty_print() {
+        let printed = known_desc().pretty_print(Network::Bitcoin).unwrap();
+        let lines = printed.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 7);
+        assert_eq!(lines[0], "Script type:        P2wpkh");
+        assert_eq!(lines[2], "Master fingerprint: 817e7be0");
+        assert_eq!(lines[3], "Origin path:        m/84'/0'/0'");
+        assert_eq!(
+            lines[6],
+            "First address:      bc1q0g0vn4yqyk0zjwxw0zv5pltyyczty004zc9g7r"
+        );
     }
 
     #[test]
-    fn test_json_descriptor() {
-        let json_descriptor = r##"{   "label": "test1",   "blockheight": 607985,   "descriptor": "wpkh([73c5da0a/84h/0h/0h]xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V/<0;1>/*)" }"##;
-        let desc = Descriptors::try_from(json_descriptor);
-        assert!(desc.is_ok());
+    fn test_fixed_descriptors() {
+        let pubkey = "0330d54fd0dd420a6e5f8d3624f5f3482cae350f79d5f0753bf5beef9c2d91af3c";
+
+        let fixed = FixedDescriptors::try_from_line(&format!("wpkh({pubkey})")).unwrap();
+        assert_eq!(
+            fixed.address(Network::Bitcoin).unwrap().to_string(),
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+        );
+        assert!(matches!(
+            fixed.address_at(1, Network::Bitcoin),
+            Err(Error::FixedIndex(1))
+        ));
+
+        let with_origin = format!("pkh([73c5da0a/44h/0h/0h/0/0]{pubkey})");
+        assert!(FixedDescriptors::try_from_line(&with_origin).is_ok());
+
+        // Descriptors still needs an extended key with both keychains
+        assert!(Descriptors::try_from_line(&format!("wpkh({pubkey})")).is_err());
+    }
+
+    #[test]
+    fn test_fixed_descriptors_rejects_ranged_and_other_scripts() {
+        let uncompressed = "0479be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8";
+        let pubkey = "0330d54fd0dd420a6e5f8d3624f5f3482cae350f79d5f0753bf5beef9c2d91af3c";
+
+        let invalid = [
+            known_desc().external.to_string(),
+            format!("pkh({uncompressed})"),
+            format!("sh(wpkh({pubkey}))"),
+            format!("wsh(pk({pubkey}))"),
+        ];
+
+        for line in invalid {
+            assert!(
+                matches!(
+                    FixedDescriptors::try_from_line(&line),
+                    Err(Error::NotFixedSinglePubkey)
+                ),
+                "{line}"
+            );
+        }
     }
 }
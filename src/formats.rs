@@ -1,105 +1,936 @@
+use std::{borrow::Cow, collections::HashSet, io::Read as _, path::Path, str::FromStr as _};
+
+use bitcoin::bip32::{ChildNumber, Fingerprint};
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    bsms::{self, Bsms},
     descriptor::{self, Descriptors},
-    json::{self, GenericJson},
+    json::{self, GenericJson, SingleSig},
+    key_expression::KeyExpression,
+    multisig::{self, MultisigDescriptors},
+    script_type::ScriptType,
+    wallet_policy::{self, WalletPolicy},
+    xpub::{self, ByteOrder},
 };
 
+#[cfg(feature = "ur")]
+use crate::ur;
+
 #[derive(Debug, Clone, Serialize, Deserialize, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Object))]
+// boxing `Json` would change the public variant, the size difference is fine for a parse result
+#[allow(clippy::large_enum_variant)]
 pub enum Format {
     Descriptor(Descriptors),
     Json(Json),
     Wasabi(Descriptors),
     Electrum(Descriptors),
+    Bitbox02(Descriptors),
+    LedgerLive(Descriptors),
+    MultiAccount(Vec<Account>),
+
+    /// One entry per active receive and change pair in `listdescriptors`
+    BitcoinCore(Vec<Descriptors>),
+
+    /// BIP388 wallet policy, `{"template": "wpkh(@0/**)", "keys": [...]}`
+    WalletPolicy(Descriptors),
+
+    /// BIP129 descriptor record, `BSMS 1.0` followed by a descriptor template
+    Bsms(Descriptors),
+
+    /// Sparrow's own wallet export, single sig with `scriptType` and one keystore, or multisig
+    /// with the wallet `descriptor` and the cosigner labels
+    Sparrow(Descriptors),
+}
+
+/// The kind of input a [`Format`] was parsed from, without the parsed data
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    derive_more::Display,
+)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum FormatKind {
+    Descriptor,
+    Json,
+    Wasabi,
+    Electrum,
+    Bitbox02,
+    LedgerLive,
+    BitcoinCore,
+    WalletPolicy,
+    Bsms,
+    Sparrow,
+}
+
+/// Base64 of `BIE1`, the start of an Electrum wallet file encrypted as a whole
+const ELECTRUM_ENCRYPTED_PREFIX: &str = "QklFMQ";
+
+/// Order formats are tried in when there is no hint, descriptors are tried last
+const CASCADE: [FormatKind; 10] = [
+    FormatKind::Json,
+    FormatKind::Wasabi,
+    FormatKind::Electrum,
+    FormatKind::Bitbox02,
+    FormatKind::LedgerLive,
+    FormatKind::Sparrow,
+    FormatKind::BitcoinCore,
+    FormatKind::WalletPolicy,
+    FormatKind::Bsms,
+    FormatKind::Descriptor,
+];
+
+/// The formats in [`CASCADE`] that are JSON documents
+const JSON_CASCADE: [FormatKind; 8] = [
+    FormatKind::Json,
+    FormatKind::Wasabi,
+    FormatKind::Electrum,
+    FormatKind::Bitbox02,
+    FormatKind::LedgerLive,
+    FormatKind::Sparrow,
+    FormatKind::BitcoinCore,
+    FormatKind::WalletPolicy,
+];
+
+impl FormatKind {
+    /// Guess the format from a file extension, with or without the `.`: `json` for the JSON
+    /// exports, `txt`, `desc` or `descriptor` for descriptors and `bsms` for BSMS files
+    ///
+    /// Only a hint for [`Format::try_new_from_str_hinted`], the contents still decide the format.
+    pub fn likely_from_extension(extension: &str) -> Option<Self> {
+        let extension = extension.trim_start_matches('.').to_ascii_lowercase();
+
+        match extension.as_str() {
+            "json" => Some(Self::Json),
+            "txt" | "desc" | "descriptor" => Some(Self::Descriptor),
+            "bsms" => Some(Self::Bsms),
+            _ => None,
+        }
+    }
+}
+
+/// A parsed [`Format`] together with the exact input it was parsed from
+#[derive(Debug, Clone, Serialize, Deserialize, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Object))]
+pub struct ParsedFormat {
+    pub original: String,
+    pub format: Format,
+    pub kind: FormatKind,
 }
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
-    #[error("Invalid descriptor: {0:?}")]
+    #[error("Invalid descriptor: {0}")]
     InvalidDescriptor(#[from] descriptor::Error),
 
     #[error("Invalid json: {0}")]
     InvalidJsonParse(#[from] serde_json::Error),
 
+    #[error("Invalid multisig wallet: {0}")]
+    InvalidMultisig(#[from] multisig::Error),
+
+    #[error("Invalid wallet policy: {0}")]
+    InvalidWalletPolicy(#[from] wallet_policy::Error),
+
+    #[error("Invalid BSMS file: {0}")]
+    InvalidBsms(#[from] bsms::Error),
+
+    #[cfg(feature = "ur")]
+    #[error("Invalid UR: {0}")]
+    InvalidUr(#[from] ur::Error),
+
     #[error("Unable to create descriptor from json")]
     InvalidDescriptorInJson,
 
     #[error("Invalid json, no xpubs or descriptor")]
     JsonNoDecriptor,
+
+    #[error(
+        "Script types in the export are for different accounts, expected {expected} found {found}"
+    )]
+    AccountMismatch { expected: u32, found: u32 },
+
+    #[error("Got an address ({0}), import needs an xpub or a descriptor instead")]
+    GotAddressNotDescriptor(String),
+
+    #[error("Unable to read file: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Drop the UTF-8 byte order mark and the `\r` of CRLF line endings that files saved by Windows
+/// tools have
+fn normalize_input(string: &str) -> Cow<'_, str> {
+    let string = string.strip_prefix('\u{feff}').unwrap_or(string);
+
+    match string.contains("\r\n") {
+        true => Cow::Owned(string.replace("\r\n", "\n")),
+        false => Cow::Borrowed(string),
+    }
+}
+
+impl Error {
+    /// The message of this error and every error in its [`source`](std::error::Error::source)
+    /// chain, joined with `: `, so the root cause shows up in a single line
+    ///
+    /// A message that is already part of the one before it is skipped.
+    pub fn root_cause_message(&self) -> String {
+        let mut messages = vec![self.to_string()];
+
+        let mut source = std::error::Error::source(self);
+        while let Some(error) = source {
+            let message = error.to_string();
+            if !messages.iter().any(|previous| previous.contains(&message)) {
+                messages.push(message);
+            }
+
+            source = error.source();
+        }
+
+        messages.join(": ")
+    }
+
+    /// Whether the input was recognized as a password protected Electrum wallet
+    fn is_encrypted_wallet(&self) -> bool {
+        let mut error: Option<&(dyn std::error::Error + 'static)> = Some(self);
+        while let Some(current) = error {
+            if let Some(descriptor::Error::EncryptedWalletUnsupported) = current.downcast_ref() {
+                return true;
+            }
+
+            error = current.source();
+        }
+
+        false
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Object))]
+#[non_exhaustive]
 pub struct Json {
     pub bip44: Option<Descriptors>,
     pub bip49: Option<Descriptors>,
     pub bip84: Option<Descriptors>,
+    pub bip86: Option<Descriptors>,
+
+    /// The `account` number from the export, Coldcard includes it since firmware 4.1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account: Option<u32>,
+}
+
+/// One account of a generic JSON export with an `accounts` array
+#[derive(Debug, Clone, Serialize, Deserialize, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Object))]
+pub struct Account {
+    /// The account number, its position in the `accounts` array when the export leaves it out
+    pub index: u32,
+    pub fingerprint: Fingerprint,
+    pub json: Json,
 }
 
 impl TryFrom<GenericJson> for Json {
     type Error = Error;
 
     fn try_from(json: GenericJson) -> Result<Self, Self::Error> {
-        if json.bip44.is_none() && json.bip49.is_none() && json.bip84.is_none() {
+        Self::try_from_generic(json, false)
+    }
+}
+
+impl Json {
+    /// Same as the [`GenericJson`] conversion, but errors if the `xfp` doesn't match the master
+    /// `xpub`, see [`descriptor::verify_master_fingerprint`], or a `first` address doesn't match
+    ///
+    /// Without the master `xpub` the `xfp` is checked against the account xpubs, which is an
+    /// [`descriptor::Error::FingerprintUnverifiable`].
+    pub fn try_from_generic_verified(json: GenericJson) -> Result<Self, Error> {
+        Self::try_from_generic(json, true)
+    }
+
+    fn try_from_generic(json: GenericJson, verified: bool) -> Result<Self, Error> {
+        if json.bip44.is_none()
+            && json.bip49.is_none()
+            && json.bip84.is_none()
+            && json.bip86.is_none()
+        {
             return Err(Error::JsonNoDecriptor);
         }
 
-        let bip44 = json
-            .bip44
-            .map(|single_sig| Descriptors::try_from_single_sig(single_sig, json.xfp.as_deref()))
-            .transpose()?;
+        let xfp = json.xfp.as_deref();
+        let mut master_verified = false;
+        if let (true, Some(master), Some(xfp)) = (verified, &json.xpub, xfp) {
+            let stated =
+                xpub::parse_xfp(xfp, ByteOrder::BigEndian).map_err(descriptor::Error::from)?;
+            let master = xpub::Xpub::try_from(master.as_str()).map_err(descriptor::Error::from)?;
+            descriptor::verify_master_fingerprint(stated, &master)?;
+            master_verified = true;
+        }
 
-        let bip49 = json
-            .bip49
-            .map(|single_sig| Descriptors::try_from_single_sig(single_sig, json.xfp.as_deref()))
-            .transpose()?;
+        let descriptors = |single_sig: Option<SingleSig>| {
+            single_sig
+                .map(|single_sig| match (verified, master_verified) {
+                    (true, true) => {
+                        Descriptors::try_from_single_sig_first_verified(single_sig, xfp)
+                    }
+                    (true, false) => Descriptors::try_from_single_sig_verified(single_sig, xfp),
+                    (false, _) => Descriptors::try_from_single_sig(single_sig, xfp),
+                })
+                .transpose()
+        };
 
-        let bip84 = json
-            .bip84
-            .map(|single_sig| Descriptors::try_from_single_sig(single_sig, json.xfp.as_deref()))
-            .transpose()?;
+        let bip44 = descriptors(json.bip44)?;
+        let bip49 = descriptors(json.bip49)?;
+        let bip84 = descriptors(json.bip84)?;
+        let bip86 = descriptors(json.bip86)?;
 
-        if bip44.is_none() && bip49.is_none() && bip84.is_none() {
+        if bip44.is_none() && bip49.is_none() && bip84.is_none() && bip86.is_none() {
             return Err(Error::JsonNoDecriptor);
         }
 
-        Ok(Json {
+        let json = Json {
             bip44,
             bip49,
             bip84,
+            bip86,
+            account: json.account,
+        };
+
+        // the account is the third step of each origin path, `84h/0h/1h` is account 1
+        let mut expected = json.account;
+        for desc in json.descriptors() {
+            let Some(ChildNumber::Hardened { index: found }) = desc
+                .origin_derivation_path()
+                .and_then(|path| path.as_ref().get(2).copied())
+            else {
+                continue;
+            };
+
+            let expected = *expected.get_or_insert(found);
+            if expected != found {
+                return Err(Error::AccountMismatch { expected, found });
+            }
+        }
+
+        Ok(json)
+    }
+}
+
+impl Account {
+    /// Every account of an export with an `accounts` array, the top level account first if the
+    /// export has one
+    fn try_from_generic_json(json: json::GenericJson) -> Result<Vec<Self>, Error> {
+        let mut accounts = Vec::with_capacity(json.accounts.len() + 1);
+
+        let has_top_level = [&json.bip44, &json.bip49, &json.bip84, &json.bip86]
+            .iter()
+            .any(|single_sig| single_sig.is_some());
+
+        if has_top_level {
+            let account = json::GenericAccountJson {
+                account: json.account,
+                xfp: json.xfp.clone(),
+                bip44: json.bip44,
+                bip49: json.bip49,
+                bip84: json.bip84,
+                bip86: json.bip86,
+            };
+
+            accounts.push(Self::try_from_account_json(account, None, 0)?);
+        }
+
+        for (position, account) in json.accounts.into_iter().enumerate() {
+            let account = Self::try_from_account_json(account, json.xfp.as_deref(), position)?;
+            accounts.push(account);
+        }
+
+        Ok(accounts)
+    }
+
+    fn try_from_account_json(
+        account: json::GenericAccountJson,
+        xfp: Option<&str>,
+        position: usize,
+    ) -> Result<Self, Error> {
+        let xfp = account.xfp.as_deref().or(xfp);
+        let fingerprint = xfp.ok_or(descriptor::Error::MissingFingerprint)?;
+        let fingerprint =
+            xpub::parse_xfp(fingerprint, ByteOrder::BigEndian).map_err(descriptor::Error::from)?;
+
+        let json = Json::try_from(json::GenericJson {
+            chain: None,
+            xfp: xfp.map(str::to_string),
+            xpub: None,
+            account: account.account,
+            bip44: account.bip44,
+            bip49: account.bip49,
+            bip84: account.bip84,
+            bip86: account.bip86,
+            accounts: Vec::new(),
+        })?;
+
+        Ok(Self {
+            index: account.account.unwrap_or(position as u32),
+            fingerprint,
+            json,
+        })
+    }
+}
+
+impl Json {
+    /// The descriptors for every script type in the export
+    pub fn descriptors(&self) -> Vec<&Descriptors> {
+        [&self.bip44, &self.bip49, &self.bip84, &self.bip86]
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    fn with_master_fingerprint(self, fingerprint: Fingerprint) -> Result<Self, Error> {
+        let set = |desc: Option<Descriptors>| {
+            desc.map(|desc| desc.with_master_fingerprint(fingerprint))
+                .transpose()
+        };
+
+        Ok(Json {
+            bip44: set(self.bip44)?,
+            bip49: set(self.bip49)?,
+            bip84: set(self.bip84)?,
+            bip86: set(self.bip86)?,
+            account: self.account,
+        })
+    }
+
+    /// Synthesize descriptors from a bare account xpub, see [`Descriptors::try_from_child_xpub`]
+    ///
+    /// A `zpub` or `ypub` only gets the script type its prefix implies, a plain `xpub` gets all
+    /// four (including taproot) since the prefix doesn't say which one the wallet uses.
+    pub fn try_from_child_xpub(xpub: &str) -> Result<Self, Error> {
+        let xpub = xpub.trim();
+        let parsed = crate::xpub::Xpub::try_from(xpub).map_err(descriptor::Error::from)?;
+
+        let wanted = ScriptType::from_slip132(&parsed.original_format());
+        let descriptors = |script_type| {
+            if wanted.is_some_and(|wanted| wanted != script_type) {
+                return Ok(None);
+            }
+
+            Descriptors::try_from_child_xpub(xpub, script_type).map(Some)
+        };
+
+        Ok(Json {
+            bip44: descriptors(ScriptType::P2pkh)?,
+            bip49: descriptors(ScriptType::P2shP2wpkh)?,
+            bip84: descriptors(ScriptType::P2wpkh)?,
+            bip86: descriptors(ScriptType::P2tr)?,
+            account: None,
         })
     }
 }
 
 impl Format {
     pub fn try_new_from_str(string: &str) -> Result<Self, Error> {
-        if let Ok(json) = serde_json::from_str::<json::GenericJson>(string) {
-            if let Ok(json) = Json::try_from(json) {
-                return Ok(Format::Json(json));
+        Self::try_new_from_str_hinted(string, None)
+    }
+
+    /// Same as [`Format::try_new_from_str`], but the `hint` format is tried first, see
+    /// [`FormatKind::likely_from_extension`]
+    ///
+    /// The hint is only advisory, every other format is still tried if it doesn't parse. When
+    /// nothing parses the error is the one for the hinted format, so a broken `.json` file gives
+    /// a JSON error.
+    pub fn try_new_from_str_hinted(string: &str, hint: Option<FormatKind>) -> Result<Self, Error> {
+        // before any format parses it, the JSON ones would otherwise read all of it first
+        if string.len() > descriptor::MAX_INPUT_BYTES {
+            return Err(descriptor::Error::InputTooLarge.into());
+        }
+
+        let string = &*normalize_input(string);
+
+        let mut hinted_error = None;
+        if let Some(kind) = hint {
+            match Self::try_new_as(string, kind) {
+                Ok(format) => return Ok(format),
+                Err(error) => hinted_error = Some(error),
             }
         }
 
-        if let Ok(json) = serde_json::from_str::<json::WasabiJson>(string) {
-            if let Ok(desc) = Descriptors::try_from(json) {
-                return Ok(Format::Wasabi(desc));
+        let mut last_error = None;
+        for kind in CASCADE {
+            if Some(kind) == hint {
+                continue;
             }
+
+            match Self::try_new_as(string, kind) {
+                Ok(format) => return Ok(format),
+                // no later format can read it, and the descriptor error would hide the reason
+                Err(error) if error.is_encrypted_wallet() => return Err(error),
+                Err(error) => last_error = Some(error),
+            }
+        }
+
+        // without a hint this is the descriptor error, the cascade tries descriptors last
+        Err(hinted_error
+            .or(last_error)
+            .expect("the cascade always tries at least one format"))
+    }
+
+    /// Parse the UR an airgapped signer shows as a QR code, all the parts of an animated one
+    ///
+    /// A `crypto-account` gives [`Format::Json`] with a descriptor per script type, a
+    /// `crypto-output` gives [`Format::Descriptor`] and a `crypto-hdkey` is read like a key
+    /// expression, or a bare xpub when it has no origin. Parts can be in any order, only the
+    /// first `seqLen` parts are used so every one of them has to be scanned.
+    #[cfg(feature = "ur")]
+    pub fn try_from_ur(parts: &[&str]) -> Result<Self, Error> {
+        match ur::decode(parts)? {
+            ur::Payload::Account(lines) => {
+                let mut json = Json {
+                    bip44: None,
+                    bip49: None,
+                    bip84: None,
+                    bip86: None,
+                    account: None,
+                };
+
+                for line in lines {
+                    let desc = Descriptors::try_from_line(&line)?;
+                    let slot = match desc.script_type() {
+                        Some(ScriptType::P2pkh) => &mut json.bip44,
+                        Some(ScriptType::P2shP2wpkh) => &mut json.bip49,
+                        Some(ScriptType::P2wpkh) => &mut json.bip84,
+                        Some(ScriptType::P2tr) => &mut json.bip86,
+                        None => continue,
+                    };
+
+                    slot.get_or_insert(desc);
+                }
+
+                if json.bip44.is_none()
+                    && json.bip49.is_none()
+                    && json.bip84.is_none()
+                    && json.bip86.is_none()
+                {
+                    return Err(ur::Error::NoSingleSigOutputs.into());
+                }
+
+                Ok(Format::Json(json))
+            }
+            ur::Payload::Output(line) => Ok(Format::Descriptor(Descriptors::try_from_line(&line)?)),
+            ur::Payload::HdKey(key) => Self::try_new_from_str(&key),
+        }
+    }
+
+    /// Parse a JSON export that is already a [`serde_json::Value`], like one nested in a larger
+    /// document, without writing it back out to a string first
+    ///
+    /// Every wallet export format is tried first, then a `{"descriptor": ..}` object or an array
+    /// of descriptor strings. A string value is parsed as the text it holds, a descriptor or a
+    /// bare xpub. When nothing matches the error is [`Error::JsonNoDecriptor`], as the errors of
+    /// the single formats would only say why the last one tried didn't fit.
+    pub fn try_from_json_value(value: serde_json::Value) -> Result<Self, Error> {
+        if let serde_json::Value::String(string) = &value {
+            return Self::try_new_from_str(string);
+        }
+
+        for kind in JSON_CASCADE {
+            if let Ok(format) = Self::try_from_json_value_as(&value, kind) {
+                return Ok(format);
+            }
+        }
+
+        if value.is_object() || value.is_array() {
+            if let Ok(descriptors) = Descriptors::try_from(value.to_string().as_str()) {
+                return Ok(Format::Descriptor(descriptors));
+            }
+        }
+
+        Err(Error::JsonNoDecriptor)
+    }
+
+    fn try_new_as(string: &str, kind: FormatKind) -> Result<Self, Error> {
+        match kind {
+            FormatKind::Json => match serde_json::from_str::<serde_json::Value>(string) {
+                Ok(value) => Self::try_from_json_value_as(&value, kind),
+                // a bare xpub is turned into the same per script type descriptors
+                Err(error) => {
+                    let json = Json::try_from_child_xpub(string)
+                        .map_err(|_| Error::InvalidJsonParse(error))?;
+                    Ok(Format::Json(json))
+                }
+            },
+            FormatKind::Electrum if string.trim_start().starts_with(ELECTRUM_ENCRYPTED_PREFIX) => {
+                Err(descriptor::Error::EncryptedWalletUnsupported.into())
+            }
+            FormatKind::Bsms => {
+                let bsms = Bsms::try_from_str(string)?;
+                Ok(Format::Bsms(bsms.to_descriptors()?))
+            }
+            FormatKind::Descriptor => {
+                // a UR pasted as text, a multi-part one with a part on each line
+                #[cfg(feature = "ur")]
+                if string
+                    .trim()
+                    .get(..3)
+                    .is_some_and(|prefix| prefix.eq_ignore_ascii_case("ur:"))
+                {
+                    let parts = string
+                        .lines()
+                        .map(str::trim)
+                        .filter(|line| !line.is_empty())
+                        .collect::<Vec<_>>();
+
+                    return Self::try_from_ur(&parts);
+                }
+
+                if let Ok(key) = KeyExpression::try_from_str(string) {
+                    if key.has_origin() {
+                        let desc = Descriptors::try_from_key_expression(&key)?;
+                        return Ok(Format::Descriptor(desc));
+                    }
+                }
+
+                match Descriptors::try_from(string) {
+                    Ok(desc) => Ok(Format::Descriptor(desc)),
+                    Err(error) => {
+                        let string = string.trim();
+                        if bitcoin::Address::from_str(string).is_ok() {
+                            return Err(Error::GotAddressNotDescriptor(string.to_string()));
+                        }
+
+                        if xpub::Xpub::try_from(string).is_ok_and(|xpub| xpub.depth() == 0) {
+                            return Err(descriptor::Error::MasterXpub.into());
+                        }
+
+                        Err(error.into())
+                    }
+                }
+            }
+            _ => {
+                let value = serde_json::from_str::<serde_json::Value>(string)?;
+                Self::try_from_json_value_as(&value, kind)
+            }
+        }
+    }
+
+    fn try_from_json_value_as(value: &serde_json::Value, kind: FormatKind) -> Result<Self, Error> {
+        match kind {
+            FormatKind::Json => {
+                let json = json::GenericJson::deserialize(value)?;
+                if !json.accounts.is_empty() {
+                    return Ok(Format::MultiAccount(Account::try_from_generic_json(json)?));
+                }
+
+                Ok(Format::Json(Json::try_from(json)?))
+            }
+            FormatKind::Wasabi => {
+                let json = json::WasabiJson::deserialize(value)?;
+                Ok(Format::Wasabi(Descriptors::try_from(json)?))
+            }
+            FormatKind::Electrum => {
+                // a password protected wallet may be missing the keystore fields, the multisig
+                // form only needs the header so it reports the encryption instead
+                if let Ok(json) = json::ElectrumJson::deserialize(value) {
+                    return Ok(Format::Electrum(Descriptors::try_from(json)?));
+                }
+
+                let json = json::ElectrumMultisigJson::deserialize(value)?;
+                let multisig = MultisigDescriptors::try_from(json)?;
+                Ok(Format::Electrum(multisig.descriptors))
+            }
+            FormatKind::Bitbox02 => {
+                let json = json::Bitbox02Json::deserialize(value)?;
+                Ok(Format::Bitbox02(Descriptors::try_from(json)?))
+            }
+            FormatKind::LedgerLive => {
+                let json = json::LedgerLiveJson::deserialize(value)?;
+                Ok(Format::LedgerLive(Descriptors::try_from(json)?))
+            }
+            FormatKind::Sparrow => {
+                // multisig exports have the wallet descriptor instead of a script type
+                if let Ok(json) = json::SparrowMultisigJson::deserialize(value) {
+                    let multisig = MultisigDescriptors::try_from(json)?;
+                    return Ok(Format::Sparrow(multisig.descriptors));
+                }
+
+                let json = json::SparrowSingleSigJson::deserialize(value)?;
+                Ok(Format::Sparrow(Descriptors::try_from(json)?))
+            }
+            FormatKind::BitcoinCore => {
+                let json = match json::BitcoinCoreJson::deserialize(value) {
+                    Ok(json) => json,
+                    Err(error) => Vec::<json::BitcoinCoreDescriptor>::deserialize(value)
+                        .map_err(|_| error)?
+                        .into(),
+                };
+                let descriptors = Descriptors::try_from_bitcoin_core(json)?;
+                Ok(Format::BitcoinCore(descriptors))
+            }
+            FormatKind::WalletPolicy => {
+                let policy = WalletPolicy::deserialize(value)?;
+                Ok(Format::WalletPolicy(policy.to_descriptors()?))
+            }
+            FormatKind::Bsms | FormatKind::Descriptor => Err(Error::JsonNoDecriptor),
+        }
+    }
+
+    /// The master fingerprint of the wallet without building any descriptors, for grouping
+    /// imports by device before parsing them in full
+    ///
+    /// Looks at the generic JSON `xfp`, Wasabi `MasterFingerprint`, Electrum keystore, BitBox02
+    /// `rootFingerprint` or the first key origin of a descriptor, or of the first descriptor in
+    /// Bitcoin Core's `listdescriptors`. `None` if the format has no
+    /// fingerprint, a Ledger Live account or a bare xpub.
+    pub fn peek_fingerprint(string: &str) -> Option<Fingerprint> {
+        let string = string.trim();
+
+        let origin_fingerprint = |desc: &str| {
+            let (_, origin) = desc.split_once('[')?;
+            Fingerprint::from_str(origin.get(..8)?).ok()
+        };
+
+        let Ok(json) = serde_json::from_str::<serde_json::Value>(string) else {
+            return origin_fingerprint(string);
+        };
+
+        // `listdescriptors` output, the bare array `importdescriptors` takes, or an array of
+        // descriptor strings
+        let core_desc = json
+            .pointer("/descriptors/0/desc")
+            .or_else(|| json.pointer("/0/desc"))
+            .or_else(|| json.pointer("/0").filter(|desc| desc.is_string()));
+
+        if let Some(desc) = core_desc {
+            return origin_fingerprint(desc.as_str()?);
+        }
+
+        let xfp = [
+            "/xfp",
+            "/MasterFingerprint",
+            "/bitcoinSimple/keyInfo/rootFingerprint",
+            "/keystores/0/keyDerivation/masterFingerprint",
+        ]
+        .into_iter()
+        .find_map(|pointer| json.pointer(pointer)?.as_str());
+
+        if let Some(xfp) = xfp {
+            return xpub::parse_xfp(xfp, ByteOrder::BigEndian).ok();
         }
 
-        if let Ok(json) = serde_json::from_str::<json::ElectrumJson>(string) {
-            if let Ok(desc) = Descriptors::try_from(json) {
-                return Ok(Format::Electrum(desc));
+        // single sig electrum files have a `keystore`, multisig ones start at `x1/`
+        let keystore = json.get("keystore").or_else(|| json.get("x1/"))?;
+        let keystore = serde_json::from_value::<json::Keystore>(keystore.clone()).ok()?;
+        let xpub = xpub::Xpub::try_from(keystore.xpub.as_str()).ok()?;
+
+        descriptor::electrum_fingerprint(&keystore, &xpub).ok()
+    }
+
+    /// Read the file at `path` and parse it with [`Format::try_new_from_str_hinted`], using the
+    /// file extension as the hint
+    ///
+    /// Files larger than [`descriptor::MAX_INPUT_BYTES`] are rejected without reading all of it
+    pub fn try_new_from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let file = std::fs::File::open(path.as_ref())?;
+
+        // read bytes, the limit can cut a UTF-8 character in half
+        let mut bytes = Vec::new();
+        file.take(descriptor::MAX_INPUT_BYTES as u64 + 1)
+            .read_to_end(&mut bytes)?;
+
+        if bytes.len() > descriptor::MAX_INPUT_BYTES {
+            return Err(descriptor::Error::InputTooLarge.into());
+        }
+
+        let string = String::from_utf8(bytes)
+            .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))?;
+
+        let hint = path
+            .as_ref()
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(FormatKind::likely_from_extension);
+
+        Self::try_new_from_str_hinted(&string, hint)
+    }
+
+    /// Parse a paste that contains several exports separated by blank lines
+    ///
+    /// Each blank line separated chunk is parsed on its own with [`Format::try_new_from_str`], so
+    /// a failure in one chunk doesn't hide the others. Exports that contain blank lines themselves
+    /// will be split apart and fail to parse.
+    pub fn try_parse_all(string: &str) -> Vec<Result<Self, Error>> {
+        let mut chunks = Vec::new();
+        let mut chunk = String::new();
+
+        for line in string.lines() {
+            if !line.trim().is_empty() {
+                chunk.push_str(line);
+                chunk.push('\n');
+                continue;
+            }
+
+            if !chunk.is_empty() {
+                chunks.push(std::mem::take(&mut chunk));
             }
         }
 
-        let desc = Descriptors::try_from(string)?;
-        Ok(Format::Descriptor(desc))
+        if !chunk.is_empty() {
+            chunks.push(chunk);
+        }
+
+        chunks
+            .iter()
+            .map(|chunk| Self::try_new_from_str(chunk))
+            .collect()
+    }
+
+    /// Parse the string like [`Format::try_new_from_str`], keeping the original input around
+    pub fn parse_with_source(string: &str) -> Result<ParsedFormat, Error> {
+        let format = Self::try_new_from_str(string)?;
+        let kind = format.kind();
+
+        Ok(ParsedFormat {
+            original: string.to_string(),
+            format,
+            kind,
+        })
+    }
+
+    /// All the descriptors in the format, Json can contain one per script type
+    pub fn descriptors(&self) -> Vec<&Descriptors> {
+        match self {
+            Format::Descriptor(desc)
+            | Format::Wasabi(desc)
+            | Format::Electrum(desc)
+            | Format::Bitbox02(desc)
+            | Format::LedgerLive(desc)
+            | Format::WalletPolicy(desc)
+            | Format::Bsms(desc)
+            | Format::Sparrow(desc) => vec![desc],
+            Format::Json(json) => json.descriptors(),
+            Format::MultiAccount(accounts) => accounts
+                .iter()
+                .flat_map(|account| account.json.descriptors())
+                .collect(),
+            Format::BitcoinCore(descriptors) => descriptors.iter().collect(),
+        }
+    }
+
+    /// Set the real master fingerprint on every descriptor that has the `00000000` placeholder,
+    /// see [`Descriptors::with_master_fingerprint`]
+    pub fn with_master_fingerprint(self, fingerprint: Fingerprint) -> Result<Format, Error> {
+        let set = |desc: Descriptors| desc.with_master_fingerprint(fingerprint);
+
+        let format = match self {
+            Format::Descriptor(desc) => Format::Descriptor(set(desc)?),
+            Format::Wasabi(desc) => Format::Wasabi(set(desc)?),
+            Format::Electrum(desc) => Format::Electrum(set(desc)?),
+            Format::Bitbox02(desc) => Format::Bitbox02(set(desc)?),
+            Format::LedgerLive(desc) => Format::LedgerLive(set(desc)?),
+            Format::WalletPolicy(desc) => Format::WalletPolicy(set(desc)?),
+            Format::Bsms(desc) => Format::Bsms(set(desc)?),
+            Format::Sparrow(desc) => Format::Sparrow(set(desc)?),
+            Format::Json(json) => Format::Json(json.with_master_fingerprint(fingerprint)?),
+            Format::MultiAccount(accounts) => Format::MultiAccount(
+                accounts
+                    .into_iter()
+                    .map(|account| {
+                        Ok(Account {
+                            json: account.json.with_master_fingerprint(fingerprint)?,
+                            ..account
+                        })
+                    })
+                    .collect::<Result<_, Error>>()?,
+            ),
+            Format::BitcoinCore(descriptors) => {
+                Format::BitcoinCore(descriptors.into_iter().map(set).collect::<Result<_, _>>()?)
+            }
+        };
+
+        Ok(format)
+    }
+
+    /// [`Descriptors::canonical_string`] of every descriptor in the format, sorted, so the same
+    /// wallet gives the same list whichever format it was imported from
+    pub fn canonical_descriptors(&self) -> Vec<String> {
+        let mut descriptors = self
+            .descriptors()
+            .iter()
+            .map(|desc| desc.canonical_string())
+            .collect::<Vec<_>>();
+
+        descriptors.sort();
+        descriptors
+    }
+
+    /// Whether the two formats contain the same extended key, whatever script type it is used with
+    ///
+    /// A bip84 export and a bare xpub import of the same account share their key even though
+    /// the bare xpub expands to every script type.
+    pub fn shares_key_with(&self, other: &Format) -> bool {
+        let keys = self
+            .descriptors()
+            .into_iter()
+            .flat_map(Descriptors::extended_keys)
+            .collect::<HashSet<_>>();
+
+        other
+            .descriptors()
+            .into_iter()
+            .flat_map(Descriptors::extended_keys)
+            .any(|key| keys.contains(&key))
+    }
+
+    /// The threshold and cosigners of a multisig wallet, from a descriptor or an Electrum
+    /// multisig file
+    ///
+    /// `None` for single sig wallets and for formats that can hold more than one wallet
+    pub fn multisig(&self) -> Option<MultisigDescriptors> {
+        let [descriptors] = self.descriptors()[..] else {
+            return None;
+        };
+
+        if descriptors.cosigner_count() < 2 {
+            return None;
+        }
+
+        MultisigDescriptors::try_from_descriptors(descriptors.clone()).ok()
+    }
+
+    /// Whether the wallet has more than one cosigner, regardless of the format it came from
+    pub fn is_multisig(&self) -> bool {
+        self.descriptors()
+            .iter()
+            .any(|desc| desc.cosigner_count() > 1)
+    }
+
+    pub fn kind(&self) -> FormatKind {
+        match self {
+            Format::Descriptor(_) => FormatKind::Descriptor,
+            Format::Json(_) | Format::MultiAccount(_) => FormatKind::Json,
+            Format::Wasabi(_) => FormatKind::Wasabi,
+            Format::Electrum(_) => FormatKind::Electrum,
+            Format::Bitbox02(_) => FormatKind::Bitbox02,
+            Format::LedgerLive(_) => FormatKind::LedgerLive,
+            Format::BitcoinCore(_) => FormatKind::BitcoinCore,
+            Format::WalletPolicy(_) => FormatKind::WalletPolicy,
+            Format::Bsms(_) => FormatKind::Bsms,
+            Format::Sparrow(_) => FormatKind::Sparrow,
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::descriptor::Keychain;
 
     #[test]
     fn test_parse_all_formats() {
@@ -119,4 +950,713 @@ mod tests {
             assert!(format.is_ok());
         }
     }
+
+    #[test]
+    fn test_parse_with_source_keeps_original() {
+        let string = std::fs::read_to_string("test/data/new-electrum.json").unwrap();
+        let parsed = Format::parse_with_source(&string).unwrap();
+
+        assert_eq!(parsed.original, string);
+        assert_eq!(parsed.kind, FormatKind::Electrum);
+        assert_eq!(parsed.format.kind(), FormatKind::Electrum);
+    }
+
+    #[test]
+    fn test_try_new_from_path() {
+        let format = Format::try_new_from_path("test/data/coldcard-export.json");
+        assert!(matches!(format, Ok(Format::Json(_))));
+
+        let format = Format::try_new_from_path("test/data/does-not-exist.json");
+        assert!(matches!(format, Err(Error::Io(_))));
+
+        // the limit falls in the middle of the `é`
+        let path =
+            std::env::temp_dir().join(format!("pubport-{}-too-large.txt", std::process::id()));
+        let contents = format!("{}é", "a".repeat(descriptor::MAX_INPUT_BYTES - 1));
+        std::fs::write(&path, contents).unwrap();
+
+        let format = Format::try_new_from_path(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            format,
+            Err(Error::InvalidDescriptor(descriptor::Error::InputTooLarge))
+        ));
+    }
+
+    #[test]
+    fn test_multi_account_json() {
+        let json = std::fs::read_to_string("test/data/coldcard-multi-account.json").unwrap();
+        let format = Format::try_new_from_str(&json).unwrap();
+        assert_eq!(format.kind(), FormatKind::Json);
+
+        let Format::MultiAccount(accounts) = &format else {
+            panic!("expected multiple accounts, got {format:?}");
+        };
+
+        assert_eq!(accounts.len(), 2);
+        assert_eq!(format.descriptors().len(), 4);
+
+        for (index, account) in accounts.iter().enumerate() {
+            assert_eq!(account.index, index as u32);
+            assert_eq!(account.fingerprint.to_string(), "73c5da0a");
+            assert!(account.json.bip49.is_none());
+        }
+
+        let second = accounts[1].json.bip84.as_ref().unwrap();
+        let address = second.address_at(Keychain::External, 0, bitcoin::Network::Bitcoin);
+        assert_eq!(
+            address.unwrap().to_string(),
+            "bc1qku0qh0mc00y8tk0n65x2tqw4trlspak0fnjmfz"
+        );
+    }
+
+    #[test]
+    fn test_peek_fingerprint() {
+        let files = [
+            ("coldcard-export.json", "817e7be0"),
+            ("new-wasabi.json", "817e7be0"),
+            ("new-electrum.json", "817e7be0"),
+            ("bitbox02-export.json", "817e7be0"),
+            ("descriptor-2.txt", "817e7be0"),
+        ];
+
+        for (file, fingerprint) in files {
+            let string = std::fs::read_to_string(format!("test/data/{file}")).unwrap();
+            let peeked = Format::peek_fingerprint(&string).map(|fp| fp.to_string());
+            assert_eq!(peeked.as_deref(), Some(fingerprint), "{file}");
+
+            let parsed = Format::try_new_from_str(&string).unwrap();
+            assert_eq!(
+                parsed.descriptors()[0].fingerprints()[0].to_string(),
+                fingerprint
+            );
+        }
+
+        let core = std::fs::read_to_string("test/data/bitcoin-core-listdescriptors.json").unwrap();
+        let peeked = Format::peek_fingerprint(&core).map(|fp| fp.to_string());
+        assert_eq!(peeked.as_deref(), Some("73c5da0a"));
+
+        let descriptor = std::fs::read_to_string("test/data/descriptor.txt").unwrap();
+        let array = serde_json::json!([descriptor.trim()]).to_string();
+        let peeked = Format::peek_fingerprint(&array).map(|fp| fp.to_string());
+        assert_eq!(peeked.as_deref(), Some("817e7be0"));
+        assert_eq!(
+            Format::try_new_from_str(&array).unwrap(),
+            Format::try_new_from_str(&descriptor).unwrap()
+        );
+
+        let ledger = std::fs::read_to_string("test/data/ledger-live-export.json").unwrap();
+        assert_eq!(Format::peek_fingerprint(&ledger), None);
+        assert_eq!(Format::peek_fingerprint("not a wallet"), None);
+    }
+
+    #[test]
+    fn test_shares_key_with() {
+        let xpub = "xpub6BoKN14JzSFN1T3cqe9FnrwnXGAsmbgETJyeazoa3F7aMXh4XndvVrJAYyM127FsrH8KFv5XFXDroqXNfZMfsinow7xp93ueYSpnrjBBFs4";
+        let bare = Format::try_new_from_str(xpub).unwrap();
+
+        let descriptor = std::fs::read_to_string("test/data/descriptor-2.txt").unwrap();
+        let descriptor = Format::try_new_from_str(&descriptor).unwrap();
+
+        let json = std::fs::read_to_string("test/data/coldcard-export.json").unwrap();
+        let json = Format::try_new_from_str(&json).unwrap();
+
+        assert!(bare.shares_key_with(&descriptor));
+        assert!(descriptor.shares_key_with(&bare));
+        assert!(json.shares_key_with(&bare));
+
+        let other = std::fs::read_to_string("test/data/coldcard-multi-account.json").unwrap();
+        let other = Format::try_new_from_str(&other).unwrap();
+        assert!(!other.shares_key_with(&bare));
+    }
+
+    #[test]
+    fn test_bitcoin_core_listdescriptors() {
+        let json = std::fs::read_to_string("test/data/bitcoin-core-listdescriptors.json").unwrap();
+        let format = Format::try_new_from_str(&json).unwrap();
+        assert_eq!(format.kind(), FormatKind::BitcoinCore);
+
+        let descriptors = format.descriptors();
+        assert_eq!(descriptors.len(), 2);
+
+        let taproot = descriptors[0];
+        assert!(taproot
+            .external
+            .to_string()
+            .starts_with("tr([73c5da0a/86'/0'/0']"));
+        assert!(taproot.internal.to_string().contains("/1/*)"));
+
+        let address = taproot.address_at(Keychain::External, 0, bitcoin::Network::Bitcoin);
+        assert_eq!(
+            address.unwrap().to_string(),
+            "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"
+        );
+
+        for desc in descriptors {
+            assert_eq!(desc.birthday_timestamp(), Some(1690000000));
+
+            // still there after setting the fingerprint the descriptors already have
+            let fingerprint = desc.fingerprints()[0];
+            let desc = desc.with_master_fingerprint(fingerprint).unwrap();
+            assert_eq!(desc.birthday_timestamp(), Some(1690000000));
+        }
+    }
+
+    #[test]
+    fn test_bitcoin_core_importdescriptors_array() {
+        let json = std::fs::read_to_string("test/data/bitcoin-core-listdescriptors.json").unwrap();
+        let listed = Format::try_new_from_str(&json).unwrap();
+
+        let json = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+        let array = json["descriptors"].to_string();
+        assert!(array.starts_with('['));
+
+        let format = Format::try_new_from_str(&array).unwrap();
+        assert_eq!(format.kind(), FormatKind::BitcoinCore);
+        assert_eq!(format.descriptors(), listed.descriptors());
+        assert_eq!(
+            Format::peek_fingerprint(&array).unwrap().to_string(),
+            "73c5da0a"
+        );
+
+        // without an active change descriptor there is no pair
+        let receive_only = serde_json::Value::Array(
+            json["descriptors"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .filter(|desc| desc["internal"] == false)
+                .cloned()
+                .collect(),
+        );
+        let receive_only = serde_json::from_value::<Vec<json::BitcoinCoreDescriptor>>(receive_only);
+        assert!(matches!(
+            Descriptors::try_from_bitcoin_core(receive_only.unwrap().into()),
+            Err(descriptor::Error::NoActiveDescriptors)
+        ));
+    }
+
+    #[test]
+    fn test_bitcoin_core_now_timestamp() {
+        let json = std::fs::read_to_string("test/data/bitcoin-core-listdescriptors.json").unwrap();
+        let json = json.replace(r#""timestamp": 1690000000"#, r#""timestamp": "now""#);
+
+        let json = serde_json::from_str::<json::BitcoinCoreJson>(&json).unwrap();
+        let descriptors = Descriptors::try_from_bitcoin_core(json).unwrap();
+        assert_eq!(descriptors[0].birthday_timestamp(), Some(1690000100));
+
+        let json = std::fs::read_to_string("test/data/bitcoin-core-listdescriptors.json").unwrap();
+        let json = json
+            .lines()
+            .map(
+                |line| match line.trim_start().starts_with(r#""timestamp""#) {
+                    true => r#"      "timestamp": "now","#,
+                    false => line,
+                },
+            )
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let json = serde_json::from_str::<json::BitcoinCoreJson>(&json).unwrap();
+        let descriptors = Descriptors::try_from_bitcoin_core(json).unwrap();
+        assert_eq!(descriptors[0].birthday_timestamp(), None);
+    }
+
+    #[test]
+    fn test_wallet_policy() {
+        let json = r#"{
+            "name": "Cold storage",
+            "template": "wpkh(@0/**)",
+            "keys": ["[817e7be0/84'/0'/0']xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM"]
+        }"#;
+
+        let format = Format::try_new_from_str(json).unwrap();
+        assert_eq!(format.kind(), FormatKind::WalletPolicy);
+
+        assert_eq!(
+            format.descriptors()[0]
+                .address_at(Keychain::External, 0, bitcoin::Network::Bitcoin)
+                .unwrap()
+                .to_string(),
+            "bc1q0g0vn4yqyk0zjwxw0zv5pltyyczty004zc9g7r"
+        );
+    }
+
+    #[test]
+    fn test_electrum_encrypted() {
+        let json = std::fs::read_to_string("test/data/new-electrum.json").unwrap();
+        let json = json.replace(r#""use_encryption": false"#, r#""use_encryption": true"#);
+
+        let format = Format::try_new_from_str_hinted(&json, Some(FormatKind::Electrum));
+        assert!(matches!(
+            format,
+            Err(Error::InvalidDescriptor(
+                descriptor::Error::EncryptedWalletUnsupported
+            ))
+        ));
+
+        // the keystore fields can be missing when the keystore is encrypted
+        let json = r#"{"seed_version": 18, "use_encryption": true, "wallet_type": "standard", "keystore": {"type": "bip32"}}"#;
+        let format = Format::try_new_from_str_hinted(json, Some(FormatKind::Electrum));
+        assert!(matches!(format, Err(Error::InvalidMultisig(_))));
+        assert!(format
+            .unwrap_err()
+            .root_cause_message()
+            .contains("Wallet is encrypted"));
+
+        let format = Format::try_new_from_str_hinted("QklFMQOrBEE...", Some(FormatKind::Electrum));
+        assert!(matches!(
+            format,
+            Err(Error::InvalidDescriptor(
+                descriptor::Error::EncryptedWalletUnsupported
+            ))
+        ));
+
+        // without a hint the encryption is reported instead of the descriptor error
+        let json = std::fs::read_to_string("test/data/new-electrum.json").unwrap();
+        let json = json.replace(r#""use_encryption": false"#, r#""use_encryption": true"#);
+        for string in [json.as_str(), "QklFMQOrBEE..."] {
+            let format = Format::try_new_from_str(string);
+            assert!(matches!(
+                format,
+                Err(Error::InvalidDescriptor(
+                    descriptor::Error::EncryptedWalletUnsupported
+                ))
+            ));
+        }
+
+        let json =
+            r#"{"seed_version": 18, "use_encryption": true, "wallet_type": "2of3", "x1/": {}}"#;
+        let format = Format::try_new_from_str(json);
+        assert!(format.unwrap_err().is_encrypted_wallet());
+    }
+
+    #[test]
+    fn test_generic_json_verified() {
+        let string = std::fs::read_to_string("test/data/coldcard-export.json").unwrap();
+        let json = serde_json::from_str::<GenericJson>(&string).unwrap();
+        assert!(Json::try_from_generic_verified(json).is_ok());
+
+        let string = string.replace(r#""xfp": "817E7BE0""#, r#""xfp": "90645A28""#);
+        let json = serde_json::from_str::<GenericJson>(&string).unwrap();
+        assert!(matches!(
+            Json::try_from_generic_verified(json.clone()),
+            Err(Error::InvalidDescriptor(
+                descriptor::Error::FingerprintXpubMismatch { .. }
+            ))
+        ));
+
+        // the unverified conversion trusts the stated fingerprint
+        assert!(Json::try_from(json.clone()).is_ok());
+
+        // the account xpubs alone can't show the fingerprint is wrong
+        let json = GenericJson { xpub: None, ..json };
+        assert!(matches!(
+            Json::try_from_generic_verified(json),
+            Err(Error::InvalidDescriptor(
+                descriptor::Error::FingerprintUnverifiable(3)
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_generic_json_account() {
+        let string = std::fs::read_to_string("test/data/coldcard-export.json").unwrap();
+        let json = serde_json::from_str::<GenericJson>(&string).unwrap();
+        assert_eq!(Json::try_from(json).unwrap().account, Some(0));
+
+        let string = string.replace(r#""account": 0"#, r#""account": 1"#);
+        let json = serde_json::from_str::<GenericJson>(&string).unwrap();
+        assert!(matches!(
+            Json::try_from(json),
+            Err(Error::AccountMismatch {
+                expected: 1,
+                found: 0
+            })
+        ));
+    }
+
+    #[test]
+    fn test_input_too_large() {
+        let padding = " ".repeat(descriptor::MAX_INPUT_BYTES);
+        let string = std::fs::read_to_string("test/data/sparrow-export.json").unwrap();
+
+        for hint in [None, Some(FormatKind::Json), Some(FormatKind::Electrum)] {
+            let format = Format::try_new_from_str_hinted(&format!("{string}{padding}"), hint);
+            assert!(matches!(
+                format,
+                Err(Error::InvalidDescriptor(descriptor::Error::InputTooLarge))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_bom_and_crlf() {
+        let json = std::fs::read_to_string("test/data/sparrow-export.json").unwrap();
+        let format = Format::try_new_from_str(&format!("\u{feff}{json}")).unwrap();
+        assert_eq!(format, Format::try_new_from_str(&json).unwrap());
+
+        let descriptor = std::fs::read_to_string("test/data/descriptor-2.txt").unwrap();
+        let crlf = format!("\u{feff}{}", descriptor.replace('\n', "\r\n"));
+        let format = Format::try_new_from_str(&crlf).unwrap();
+        assert_eq!(format, Format::try_new_from_str(&descriptor).unwrap());
+    }
+
+    #[test]
+    fn test_likely_from_extension() {
+        assert_eq!(
+            FormatKind::likely_from_extension("json"),
+            Some(FormatKind::Json)
+        );
+        assert_eq!(
+            FormatKind::likely_from_extension(".JSON"),
+            Some(FormatKind::Json)
+        );
+        assert_eq!(
+            FormatKind::likely_from_extension("txt"),
+            Some(FormatKind::Descriptor)
+        );
+        assert_eq!(FormatKind::likely_from_extension("pdf"), None);
+    }
+
+    #[test]
+    fn test_try_from_json_value() {
+        let string = std::fs::read_to_string("test/data/new-electrum.json").unwrap();
+        let document = serde_json::json!({
+            "name": "cold storage",
+            "wallet": serde_json::from_str::<serde_json::Value>(&string).unwrap(),
+        });
+
+        let format = Format::try_from_json_value(document["wallet"].clone()).unwrap();
+        assert_eq!(format, Format::try_new_from_str(&string).unwrap());
+        assert_eq!(format.kind(), FormatKind::Electrum);
+
+        let descriptor = std::fs::read_to_string("test/data/descriptor-2.txt").unwrap();
+        let format = Format::try_from_json_value(descriptor.trim().into()).unwrap();
+        assert_eq!(format.kind(), FormatKind::Descriptor);
+
+        let line = "wpkh([817e7be0/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>/*)";
+        let expected = Format::Descriptor(Descriptors::try_from_line(line).unwrap());
+
+        let format = Format::try_from_json_value(serde_json::json!({ "descriptor": line }));
+        assert_eq!(format.unwrap(), expected);
+
+        let lines = [line.replace("<0;1>", "0"), line.replace("<0;1>", "1")];
+        let format = Format::try_from_json_value(serde_json::json!(lines)).unwrap();
+        assert_eq!(format.kind(), FormatKind::Descriptor);
+        assert_eq!(format.descriptors(), expected.descriptors());
+
+        let format = Format::try_from_json_value(serde_json::json!({ "name": "no keys" }));
+        assert!(matches!(format, Err(Error::JsonNoDecriptor)));
+    }
+
+    #[test]
+    fn test_try_new_from_str_hinted() {
+        let descriptor = std::fs::read_to_string("test/data/descriptor-2.txt").unwrap();
+
+        // a wrong hint still finds the right format
+        let format = Format::try_new_from_str_hinted(&descriptor, Some(FormatKind::Json));
+        assert!(matches!(format, Ok(Format::Descriptor(_))));
+
+        // the error is the one for the hinted format
+        let format = Format::try_new_from_str_hinted("{\"xpub\": ", Some(FormatKind::Json));
+        assert!(matches!(format, Err(Error::InvalidJsonParse(_))));
+
+        let format = Format::try_new_from_str_hinted("{\"xpub\": ", None);
+        assert!(matches!(format, Err(Error::InvalidDescriptor(_))));
+    }
+
+    #[test]
+    fn test_try_parse_all() {
+        let json = std::fs::read_to_string("test/data/coldcard-export.json").unwrap();
+        let descriptor = std::fs::read_to_string("test/data/descriptor-2.txt").unwrap();
+        let string = format!("{json}\n\n{descriptor}\n\nnot a wallet\n");
+
+        let formats = Format::try_parse_all(&string);
+        assert_eq!(formats.len(), 3);
+
+        assert!(matches!(formats[0], Ok(Format::Json(_))));
+        assert!(matches!(formats[1], Ok(Format::Descriptor(_))));
+        assert!(formats[2].is_err());
+    }
+
+    #[test]
+    fn test_address_instead_of_descriptor() {
+        for address in [
+            "bc1q0g0vn4yqyk0zjwxw0zv5pltyyczty004zc9g7r",
+            " 1FJR4DFNWEnKGQWHjm5yXKHao84JPGgGPn\n",
+        ] {
+            let format = Format::try_new_from_str(address);
+            assert!(matches!(format, Err(Error::GotAddressNotDescriptor(_))));
+        }
+    }
+
+    #[test]
+    fn test_is_multisig() {
+        let string = std::fs::read_to_string("test/data/sparrow-export.json").unwrap();
+        let format = Format::try_new_from_str(&string).unwrap();
+        assert!(!format.is_multisig());
+
+        let multisig = "wsh(sortedmulti(2,[817e7be0/48h/0h/0h/2h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>/*,[90645a28/48h/0h/0h/2h]xpub6CCKAvUTNursEnaJ8k1d27LfqEUzeAx2N9wFqYE3W1xh7nqgJEBEbLSSmohwDxzsSvcsYqiQqFzRvta65Njbe5o84bF5YXHFqfSH2Dkhonm/<0;1>/*))";
+        let format = Format::try_new_from_str(multisig).unwrap();
+        assert!(format.is_multisig());
+    }
+
+    #[test]
+    fn test_sparrow_wallet_export() {
+        let string = std::fs::read_to_string("test/data/sparrow-wallet-export.json").unwrap();
+        let format = Format::try_new_from_str(&string).unwrap();
+
+        assert_eq!(format.kind(), FormatKind::Sparrow);
+        assert_eq!(
+            format.descriptors(),
+            [&Descriptors::try_from_line("wpkh([817e7be0/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>/*)").unwrap()]
+        );
+        assert_eq!(
+            Format::peek_fingerprint(&string).unwrap().to_string(),
+            "817e7be0"
+        );
+
+        let nested = string.replace("\"P2WPKH\"", "\"P2SH_P2WPKH\"");
+        let format = Format::try_new_from_str(&nested).unwrap();
+        assert_eq!(
+            format.descriptors()[0].script_type(),
+            Some(ScriptType::P2shP2wpkh)
+        );
+
+        let json = serde_json::from_str::<json::SparrowSingleSigJson>(&string).unwrap();
+        let multisig = json::SparrowSingleSigJson {
+            keystores: vec![json.keystores[0].clone(), json.keystores[0].clone()],
+            ..json
+        };
+        assert!(matches!(
+            Descriptors::try_from(multisig),
+            Err(descriptor::Error::NotSingleSig(2))
+        ));
+    }
+
+    #[test]
+    fn test_bsms() {
+        let format = Format::try_new_from_path("test/data/bsms-descriptor-record.txt").unwrap();
+
+        assert_eq!(format.kind(), FormatKind::Bsms);
+        assert_eq!(format.multisig().unwrap().threshold, 2);
+        assert_eq!(
+            FormatKind::likely_from_extension("bsms"),
+            Some(FormatKind::Bsms)
+        );
+    }
+
+    #[test]
+    fn test_multisig() {
+        let string = std::fs::read_to_string("test/data/sparrow-export.json").unwrap();
+        assert!(Format::try_new_from_str(&string)
+            .unwrap()
+            .multisig()
+            .is_none());
+
+        let line = "wsh(sortedmulti(2,[817e7be0/48h/0h/0h/2h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>/*,[90645a28/48h/0h/0h/2h]xpub6CCKAvUTNursEnaJ8k1d27LfqEUzeAx2N9wFqYE3W1xh7nqgJEBEbLSSmohwDxzsSvcsYqiQqFzRvta65Njbe5o84bF5YXHFqfSH2Dkhonm/<0;1>/*))";
+        let multisig = Format::try_new_from_str(line).unwrap().multisig().unwrap();
+
+        assert_eq!(multisig, MultisigDescriptors::try_from_line(line).unwrap());
+        assert_eq!(multisig.threshold, 2);
+        assert_eq!(multisig.script_type, multisig::MultisigScriptType::P2wsh);
+        assert_eq!(
+            multisig
+                .cosigners
+                .iter()
+                .map(|cosigner| cosigner.fingerprint.to_string())
+                .collect::<Vec<_>>(),
+            ["817e7be0", "90645a28"]
+        );
+
+        let string = std::fs::read_to_string("test/data/electrum-multisig.json").unwrap();
+        let format = Format::try_new_from_str(&string).unwrap();
+        assert!(format.multisig().is_some());
+    }
+
+    #[test]
+    fn test_bare_xpub() {
+        let xpub = "xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM";
+        let format = Format::try_new_from_str(xpub).unwrap();
+
+        assert_eq!(format.kind(), FormatKind::Json);
+        assert_eq!(format.descriptors().len(), 4);
+        assert!(format
+            .descriptors()
+            .iter()
+            .all(|desc| desc.is_synthesized()));
+
+        let zpub = "zpub6rNrPrFwgm4wMBSysetK5tpLBS2HYT8TDKQA6amxFHKJUnQq8rNtc4JDfGYPbvF9wJyagPpG1Faqnfe3BB8XzKon8LwW9KkMWyAQ4RQHzB1";
+        let Format::Json(json) = Format::try_new_from_str(zpub).unwrap() else {
+            panic!("expected json");
+        };
+
+        assert!(json.bip84.is_some());
+        assert!(json.bip44.is_none() && json.bip49.is_none() && json.bip86.is_none());
+    }
+
+    #[test]
+    fn test_generic_json_bip86() {
+        let string = std::fs::read_to_string("test/data/taproot-export.json").unwrap();
+        let Format::Json(json) = Format::try_new_from_str(&string).unwrap() else {
+            panic!("expected json");
+        };
+
+        assert!(json.bip84.is_some());
+
+        let address = json
+            .bip86
+            .unwrap()
+            .address_at(Keychain::External, 0, bitcoin::Network::Bitcoin)
+            .unwrap();
+
+        assert_eq!(
+            address.to_string(),
+            "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"
+        );
+    }
+
+    #[test]
+    fn test_key_expression() {
+        let key = "[817e7be0/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>/*";
+        let format = Format::try_new_from_str(key).unwrap();
+
+        assert_eq!(format.kind(), FormatKind::Descriptor);
+        assert_eq!(
+            format.descriptors()[0].fingerprint().unwrap().to_string(),
+            "817e7be0"
+        );
+    }
+
+    #[test]
+    fn test_master_xpub() {
+        let master = "xpub661MyMwAqRbcFFr2SGY3dUn7g8P9VKNZdKWL2Z2pZMEkBWH2D1KTcwTn7keZQCaScCx7BUDjHFJJHnzBvDgUFgNjYsQTRvo7LWfYEtt78Pb";
+        let error = Format::try_new_from_str(master).unwrap_err();
+
+        assert!(matches!(
+            error,
+            Error::InvalidDescriptor(descriptor::Error::MasterXpub)
+        ));
+        assert!(error.root_cause_message().contains("private key"));
+    }
+
+    #[test]
+    fn test_root_cause_message() {
+        // `0` is not a base58 character
+        let xpub = "xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQ0";
+        let error = Descriptors::try_from_child_xpub(xpub, ScriptType::P2wpkh).unwrap_err();
+        let error = Error::from(error);
+
+        let message = error.root_cause_message();
+        assert!(!error.to_string().contains("invalid base58 character"));
+        assert_eq!(
+            message,
+            "Invalid descriptor: Unable to parse xpub: Invalid xpub: base58 encoding error: decode: invalid base58 character 0x30"
+        );
+    }
+
+    #[test]
+    fn test_sparrow_multisig_export() {
+        let string = std::fs::read_to_string("test/data/sparrow-multisig-export.json").unwrap();
+        let format = Format::try_new_from_str(&string).unwrap();
+
+        assert_eq!(format.kind(), FormatKind::Sparrow);
+        let multisig = format.multisig().unwrap();
+        assert_eq!(multisig.threshold, 2);
+        assert_eq!(multisig.cosigners.len(), 2);
+
+        // a bare descriptor json, without keystores, is still a descriptor
+        let json = serde_json::from_str::<json::SparrowMultisigJson>(&string).unwrap();
+        let bare = serde_json::json!({ "descriptor": json.descriptor }).to_string();
+        let format = Format::try_new_from_str(&bare).unwrap();
+        assert_eq!(format.kind(), FormatKind::Descriptor);
+        assert!(format.is_multisig());
+    }
+
+    #[test]
+    fn test_electrum_multisig() {
+        let string = std::fs::read_to_string("test/data/electrum-multisig.json").unwrap();
+        let format = Format::try_new_from_str(&string).unwrap();
+
+        assert_eq!(format.kind(), FormatKind::Electrum);
+        assert!(format.is_multisig());
+    }
+
+    #[test]
+    fn test_canonical_descriptors() {
+        let descriptor = std::fs::read_to_string("test/data/descriptor.txt").unwrap();
+        let descriptor = Format::try_new_from_str(&descriptor).unwrap();
+        assert_eq!(descriptor.canonical_descriptors().len(), 1);
+
+        let json = std::fs::read_to_string("test/data/coldcard-export.json").unwrap();
+        let json = Format::try_new_from_str(&json).unwrap();
+
+        let canonical = json.canonical_descriptors();
+        assert_eq!(canonical.len(), 3);
+        assert!(canonical.is_sorted());
+
+        // the electrum export is the bip84 account of the same wallet
+        let electrum = std::fs::read_to_string("test/data/new-electrum.json").unwrap();
+        let electrum = Format::try_new_from_str(&electrum).unwrap();
+        assert!(canonical.contains(&electrum.canonical_descriptors()[0]));
+    }
+
+    #[test]
+    fn test_with_master_fingerprint() {
+        let xpub = "xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM";
+        let format = Format::try_new_from_str(xpub).unwrap();
+
+        let fingerprint = Fingerprint::from_str("817e7be0").unwrap();
+        let format = format.with_master_fingerprint(fingerprint).unwrap();
+
+        for desc in format.descriptors() {
+            assert_eq!(desc.fingerprints(), vec![fingerprint]);
+        }
+    }
+
+    #[test]
+    fn test_electrum_camel_case() {
+        let camel_case = std::fs::read_to_string("test/data/electrum-camel-case.json").unwrap();
+        let snake_case = std::fs::read_to_string("test/data/new-electrum.json").unwrap();
+
+        let format = Format::try_new_from_str(&camel_case).unwrap();
+        assert_eq!(format.kind(), FormatKind::Electrum);
+        assert_eq!(format, Format::try_new_from_str(&snake_case).unwrap());
+    }
+
+    #[test]
+    fn test_bitbox02() {
+        // synthetic, written by hand after the BitBox02 JSON layout for the known test wallet,
+        // not a redacted export from a device
+        let string = std::fs::read_to_string("test/data/bitbox02-export.json").unwrap();
+        let format = Format::try_new_from_str(&string).unwrap();
+
+        assert_eq!(format.kind(), FormatKind::Bitbox02);
+        assert_eq!(
+            format.descriptors()[0].fingerprint().unwrap().to_string(),
+            "817e7be0"
+        );
+    }
+
+    #[test]
+    fn test_ledger_live() {
+        // synthetic, written by hand after the Ledger Live account export for the known test
+        // wallet, the `seedIdentifier` is made up and not a redacted export
+        let string = std::fs::read_to_string("test/data/ledger-live-export.json").unwrap();
+        let format = Format::try_new_from_str(&string).unwrap();
+        assert_eq!(format.kind(), FormatKind::LedgerLive);
+
+        let desc = format.descriptors()[0];
+        assert!(desc.is_synthesized());
+
+        let address = desc
+            .address_at(Keychain::External, 0, bitcoin::Network::Bitcoin)
+            .unwrap();
+
+        assert_eq!(
+            address.to_string(),
+            "bc1q0g0vn4yqyk0zjwxw0zv5pltyyczty004zc9g7r"
+        );
+    }
 }
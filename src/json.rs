@@ -1,6 +1,11 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
+use crate::script_type::ScriptType;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct GenericJson {
     #[serde(default)]
     pub chain: Option<String>,
@@ -8,43 +13,251 @@ pub struct GenericJson {
     pub xfp: Option<String>,
     #[serde(default)]
     pub xpub: Option<String>,
+    #[serde(default)]
+    pub account: Option<u32>,
     pub bip44: Option<SingleSig>,
     pub bip49: Option<SingleSig>,
     pub bip84: Option<SingleSig>,
+    pub bip86: Option<SingleSig>,
+
+    /// Newer Coldcard firmware can export several accounts in one file
+    #[serde(default)]
+    pub accounts: Vec<GenericAccountJson>,
+}
+
+/// One entry of the `accounts` array of a [`GenericJson`], the `xfp` falls back to the top level
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct GenericAccountJson {
+    #[serde(default)]
+    pub account: Option<u32>,
+    #[serde(default)]
+    pub xfp: Option<String>,
+    pub bip44: Option<SingleSig>,
+    pub bip49: Option<SingleSig>,
+    pub bip84: Option<SingleSig>,
+    pub bip86: Option<SingleSig>,
+}
+
+/// Sparrow's own wallet export, a single sig wallet has one keystore
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SparrowSingleSigJson {
+    pub script_type: ScriptType,
+    pub keystores: Vec<SparrowKeystore>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SparrowKeystore {
+    #[serde(alias = "xpub")]
+    pub extended_public_key: String,
+    pub key_derivation: SparrowKeyDerivation,
+}
+
+/// Sparrow's multisig export, the wallet descriptor and a label for each cosigner
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SparrowMultisigJson {
+    #[serde(default)]
+    pub label: Option<String>,
+    pub descriptor: String,
+    pub keystores: Vec<SparrowCosignerJson>,
+}
+
+/// A cosigner's label, matched to the descriptor key by its master fingerprint, or by position
+/// when there is no key derivation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SparrowCosignerJson {
+    pub label: String,
+    #[serde(default)]
+    pub key_derivation: Option<SparrowKeyDerivation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SparrowKeyDerivation {
+    pub master_fingerprint: String,
+    pub derivation_path: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
+#[non_exhaustive]
 pub struct WasabiJson {
     pub cold_card_firmware_version: String,
     pub master_fingerprint: String,
     pub ext_pub_key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_key_path: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ElectrumJson {
+    #[serde(alias = "seedVersion")]
     pub seed_version: u32,
+    #[serde(alias = "useEncryption")]
     pub use_encryption: bool,
+    #[serde(alias = "walletType")]
     pub wallet_type: String,
     pub keystore: Keystore,
 }
 
-// electrum
+/// Electrum multisig wallet, each cosigner's keystore is a top level `x1/`, `x2/`... field
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ElectrumMultisigJson {
+    #[serde(alias = "seedVersion")]
+    pub seed_version: u32,
+    #[serde(alias = "useEncryption")]
+    pub use_encryption: bool,
+    #[serde(alias = "walletType")]
+    pub wallet_type: String,
+
+    /// Every other top level field, the keystores as well as history, labels etc.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_json::Value>,
+}
+
+impl ElectrumMultisigJson {
+    /// The `x{n}/` keystores, in index order
+    pub fn keystores(&self) -> Result<Vec<Keystore>, serde_json::Error> {
+        let mut keystores = self
+            .extra
+            .iter()
+            .filter_map(|(key, value)| {
+                let index = key.strip_prefix('x')?.strip_suffix('/')?;
+                Some((index.parse::<u32>().ok()?, value))
+            })
+            .collect::<Vec<_>>();
+
+        keystores.sort_by_key(|(index, _)| *index);
+        keystores
+            .into_iter()
+            .map(|(_, keystore)| Keystore::deserialize(keystore))
+            .collect()
+    }
+}
+
+/// A BitBoxApp single sig account, `{"bitcoinSimple": {"scriptType": ..., "keyInfo": ...}}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bitbox02Json {
+    pub bitcoin_simple: BitboxSimple,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BitboxSimple {
+    pub script_type: ScriptType,
+    pub key_info: BitboxKeyInfo,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BitboxKeyInfo {
+    pub root_fingerprint: String,
+    pub keypath: String,
+    pub xpub: String,
+}
+
+/// A Ledger Live account, the master fingerprint is not included
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerLiveJson {
+    pub xpub: String,
+
+    /// `""` for legacy, `"segwit"`, `"native_segwit"` or `"taproot"`
+    pub derivation_mode: String,
+
+    /// The account number
+    pub index: u32,
+
+    #[serde(default)]
+    pub currency_id: Option<String>,
+}
+
+impl LedgerLiveJson {
+    pub fn script_type(&self) -> Option<ScriptType> {
+        match self.derivation_mode.as_str() {
+            "" => Some(ScriptType::P2pkh),
+            "segwit" => Some(ScriptType::P2shP2wpkh),
+            "native_segwit" => Some(ScriptType::P2wpkh),
+            "taproot" => Some(ScriptType::P2tr),
+            _ => None,
+        }
+    }
+}
+
+/// Output of Bitcoin Core's `listdescriptors`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BitcoinCoreJson {
+    #[serde(default)]
+    pub wallet_name: Option<String>,
+    pub descriptors: Vec<BitcoinCoreDescriptor>,
+}
+
+/// `importdescriptors` takes the same descriptors as a bare array, without the wallet name
+impl From<Vec<BitcoinCoreDescriptor>> for BitcoinCoreJson {
+    fn from(descriptors: Vec<BitcoinCoreDescriptor>) -> Self {
+        Self {
+            wallet_name: None,
+            descriptors,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BitcoinCoreDescriptor {
+    pub desc: String,
+    #[serde(default)]
+    pub timestamp: Option<BitcoinCoreTimestamp>,
+    #[serde(default)]
+    pub active: bool,
+    #[serde(default)]
+    pub internal: bool,
+}
+
+/// Unix time the descriptor was created at, or `"now"` in descriptors written for
+/// `importdescriptors`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BitcoinCoreTimestamp {
+    Unix(u64),
+    Other(String),
+}
+
+impl BitcoinCoreTimestamp {
+    /// The unix time, `None` for `"now"`
+    pub fn unix(&self) -> Option<u64> {
+        match self {
+            BitcoinCoreTimestamp::Unix(timestamp) => Some(*timestamp),
+            BitcoinCoreTimestamp::Other(_) => None,
+        }
+    }
+}
+
+// electrum, forks and reimplementations sometimes write the fields in camelCase
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct Keystore {
+    /// `bip32` for a watch only keystore, `hardware` for one backed by a signing device
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub keystore_type: Option<String>,
     pub derivation: String,
     pub xpub: String,
-    #[serde(default)]
+    #[serde(default, alias = "ckccXfp")]
     pub ckcc_xfp: Option<u32>,
-    #[serde(default)]
+    #[serde(default, alias = "ckccXpub", skip_serializing_if = "Option::is_none")]
     pub ckcc_xpub: Option<String>,
 }
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct SingleSig {
     #[serde(default)]
-    pub name: Option<Name>,
+    pub name: Option<ScriptType>,
     #[serde(default)]
     pub xfp: Option<String>,
     #[serde(default)]
@@ -58,18 +271,9 @@ pub struct SingleSig {
     pub first: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
-pub enum Name {
-    /// BIP44
-    P2pkh,
-
-    /// BIP49
-    P2shP2wpkh,
-
-    /// BIP84
-    P2wpkh,
-}
+/// The script type of a [`SingleSig`] entry, now the shared [`ScriptType`]
+#[deprecated(note = "use `ScriptType` instead")]
+pub type Name = ScriptType;
 
 #[cfg(test)]
 mod tests {
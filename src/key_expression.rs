@@ -0,0 +1,465 @@
+use std::str::FromStr as _;
+
+use bitcoin::bip32::{ChildNumber, DerivationPath, Fingerprint};
+
+use crate::xpub::{self, Xpub};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Invalid key origin: {0}")]
+    InvalidOrigin(String),
+
+    #[error("Invalid fingerprint in key origin: {0}")]
+    InvalidFingerprint(String),
+
+    #[error("Invalid derivation path: {0}")]
+    InvalidDerivationPath(#[from] bitcoin::bip32::Error),
+
+    #[error("Invalid xpub: {0}")]
+    InvalidXpub(#[from] xpub::Error),
+
+    #[error("Invalid derivation step after the xpub: {0}")]
+    InvalidStep(String),
+
+    #[error("Can't derive hardened children from an xpub: {0}")]
+    HardenedStep(String),
+
+    #[error("Invalid multipath step: {0}")]
+    InvalidMultipath(String),
+
+    #[error("Got an extended private key ({0}), only public keys can be imported")]
+    PrivateKeyProvided(String),
+}
+
+/// Extended private key prefixes, BIP32 and SLIP-132, mainnet and testnet
+const PRIVATE_KEY_PREFIXES: [&str; 10] = [
+    "xprv", "yprv", "zprv", "Yprv", "Zprv", "tprv", "uprv", "vprv", "Uprv", "Vprv",
+];
+
+/// How hardened derivation steps are written, `84h` or `84'`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum HardenedMarker {
+    /// `84h`, needs no quoting in shells and JSON, BSMS coordinators expect it
+    #[default]
+    H,
+
+    /// `84'`, what miniscript writes
+    Apostrophe,
+}
+
+impl HardenedMarker {
+    /// The path without the leading `m/`, `84h/0h/0h`
+    pub fn format_path(&self, path: &DerivationPath) -> String {
+        let marker = match self {
+            HardenedMarker::H => "h",
+            HardenedMarker::Apostrophe => "'",
+        };
+
+        path.into_iter()
+            .map(|child| match child {
+                ChildNumber::Hardened { index } => format!("{index}{marker}"),
+                ChildNumber::Normal { index } => index.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+/// How a whole derivation path is written, `m/84h/0h/0h` or `84'/0'/0'`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct PathFormat {
+    pub marker: HardenedMarker,
+
+    /// Start the path with `m/`, an empty path is then written as `m`
+    pub master_prefix: bool,
+}
+
+impl PathFormat {
+    /// `m/84h/0h/0h`, what Electrum and most wallet exports use
+    pub const MASTER_H: Self = Self {
+        marker: HardenedMarker::H,
+        master_prefix: true,
+    };
+
+    /// `m/84'/0'/0'`, what Wasabi and Sparrow write
+    pub const MASTER_APOSTROPHE: Self = Self {
+        marker: HardenedMarker::Apostrophe,
+        master_prefix: true,
+    };
+
+    pub fn format_path(&self, path: &DerivationPath) -> String {
+        let steps = self.marker.format_path(path);
+
+        match (self.master_prefix, steps.is_empty()) {
+            (false, _) => steps,
+            (true, true) => "m".to_string(),
+            (true, false) => format!("m/{steps}"),
+        }
+    }
+}
+
+/// A single extended key with its optional origin, a descriptor key without the script around
+/// it, `[817e7be0/84h/0h/0h]xpub.../<0;1>/*`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Object))]
+pub struct KeyExpression {
+    /// Master fingerprint from the key origin, `None` when there is no origin
+    pub fingerprint: Option<Fingerprint>,
+
+    /// Derivation path from the key origin, empty when there is no origin
+    pub origin: DerivationPath,
+
+    pub xpub: Xpub,
+
+    /// Unhardened steps after the xpub, before the multipath step
+    pub derivation: DerivationPath,
+
+    /// Receive and change indexes from a `<a;b>` step
+    pub multipath: Option<(u32, u32)>,
+
+    /// Whether the key ends in `/*`
+    pub wildcard: bool,
+}
+
+impl KeyExpression {
+    pub fn try_from_str(string: &str) -> Result<Self, Error> {
+        let string = string.trim();
+
+        let (fingerprint, origin, key) = match string.strip_prefix('[') {
+            Some(rest) => {
+                let (origin, key) = rest
+                    .split_once(']')
+                    .ok_or_else(|| Error::InvalidOrigin(string.to_string()))?;
+
+                let (fingerprint, origin) = parse_origin(origin)?;
+                (Some(fingerprint), origin, key)
+            }
+            None => (None, DerivationPath::master(), string),
+        };
+
+        let (xpub, derivation, multipath, wildcard) = parse_xpub_and_derivation(key)?;
+
+        Ok(Self {
+            fingerprint,
+            origin,
+            xpub,
+            derivation,
+            multipath,
+            wildcard,
+        })
+    }
+
+    /// Whether the key has a `[fingerprint/path]` origin
+    pub fn has_origin(&self) -> bool {
+        self.fingerprint.is_some()
+    }
+
+    /// Same as the [`Display`](std::fmt::Display) form, with the origin path written with the
+    /// given hardened marker, `[817e7be0/84h/0h/0h]xpub.../<0;1>/*`
+    pub fn to_string_with_marker(&self, marker: HardenedMarker) -> String {
+        let mut string = String::new();
+        self.write_with_marker(&mut string, marker)
+            .expect("writing to a string never fails");
+
+        string
+    }
+
+    fn write_with_marker(
+        &self,
+        f: &mut impl std::fmt::Write,
+        marker: HardenedMarker,
+    ) -> std::fmt::Result {
+        match self.fingerprint {
+            Some(fingerprint) if self.origin.is_empty() => write!(f, "[{fingerprint}]")?,
+            Some(fingerprint) => write!(f, "[{fingerprint}/{}]", marker.format_path(&self.origin))?,
+            None => (),
+        }
+
+        write!(f, "{}", self.xpub)?;
+
+        for child in &self.derivation {
+            write!(f, "/{child}")?;
+        }
+
+        if let Some((receive, change)) = self.multipath {
+            write!(f, "/<{receive};{change}>")?;
+        }
+
+        if self.wildcard {
+            write!(f, "/*")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Canonical form of the key, `[817e7be0/84'/0'/0']xpub.../<0;1>/*`
+///
+/// BIP380 origins always start with the fingerprint, so an origin path without a fingerprint is
+/// left out. SLIP-132 keys are written as their `xpub`.
+impl std::fmt::Display for KeyExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_with_marker(f, HardenedMarker::Apostrophe)
+    }
+}
+
+/// `817e7be0/84h/0h/0h` from inside the origin brackets, the `m/` some tools add is accepted
+fn parse_origin(origin: &str) -> Result<(Fingerprint, DerivationPath), Error> {
+    let (fingerprint, path) = origin.split_once('/').unwrap_or((origin, ""));
+
+    let fingerprint = Fingerprint::from_str(fingerprint)
+        .map_err(|_| Error::InvalidFingerprint(fingerprint.to_string()))?;
+
+    let path = path.strip_prefix("m/").unwrap_or(path);
+    let path = match path {
+        "" | "m" => DerivationPath::master(),
+        path => DerivationPath::from_str(&format!("m/{path}"))?,
+    };
+
+    Ok((fingerprint, path))
+}
+
+type XpubAndDerivation = (Xpub, DerivationPath, Option<(u32, u32)>, bool);
+
+/// `xpub.../0/<0;1>/*`, plain steps then an optional multipath step and an optional wildcard
+fn parse_xpub_and_derivation(key: &str) -> Result<XpubAndDerivation, Error> {
+    let mut steps = key.split('/');
+    let xpub = steps.next().unwrap_or_default();
+
+    // only the prefix goes in the error, never the key
+    if let Some(prefix) = PRIVATE_KEY_PREFIXES
+        .into_iter()
+        .find(|prefix| xpub.starts_with(prefix))
+    {
+        return Err(Error::PrivateKeyProvided(prefix.to_string()));
+    }
+
+    let xpub = Xpub::try_from(xpub)?;
+
+    let mut derivation = Vec::new();
+    let mut multipath = None;
+    let mut wildcard = false;
+
+    for step in steps {
+        if wildcard {
+            return Err(Error::InvalidStep(step.to_string()));
+        }
+
+        if step == "*" {
+            wildcard = true;
+            continue;
+        }
+
+        if let Some(indexes) = step.strip_prefix('<').and_then(|s| s.strip_suffix('>')) {
+            if multipath.is_some() {
+                return Err(Error::InvalidMultipath(step.to_string()));
+            }
+
+            let (receive, change) = indexes
+                .split_once(';')
+                .ok_or_else(|| Error::InvalidMultipath(step.to_string()))?;
+
+            let index = |index: &str| {
+                index
+                    .parse::<u32>()
+                    .map_err(|_| Error::InvalidMultipath(step.to_string()))
+            };
+
+            multipath = Some((index(receive)?, index(change)?));
+            continue;
+        }
+
+        if multipath.is_some() {
+            return Err(Error::InvalidStep(step.to_string()));
+        }
+
+        let child =
+            ChildNumber::from_str(step).map_err(|_| Error::InvalidStep(step.to_string()))?;
+
+        if child.is_hardened() {
+            return Err(Error::HardenedStep(step.to_string()));
+        }
+
+        derivation.push(child);
+    }
+
+    Ok((xpub, derivation.into(), multipath, wildcard))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XPUB: &str = "xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM";
+
+    #[test]
+    fn test_parse_with_origin_and_multipath() {
+        let key =
+            KeyExpression::try_from_str(&format!("[817e7be0/84h/0h/0h]{XPUB}/<0;1>/*")).unwrap();
+
+        assert_eq!(key.fingerprint.unwrap().to_string(), "817e7be0");
+        assert_eq!(key.origin, DerivationPath::from_str("m/84h/0h/0h").unwrap());
+        assert_eq!(key.xpub.as_str(), XPUB);
+        assert!(key.derivation.is_empty());
+        assert_eq!(key.multipath, Some((0, 1)));
+        assert!(key.wildcard);
+    }
+
+    #[test]
+    fn test_parse_without_origin() {
+        let key = KeyExpression::try_from_str(&format!("{XPUB}/0/*")).unwrap();
+
+        assert!(!key.has_origin());
+        assert!(key.origin.is_empty());
+        assert_eq!(key.derivation, DerivationPath::from_str("m/0").unwrap());
+        assert_eq!(key.multipath, None);
+    }
+
+    #[test]
+    fn test_parse_multipath_without_origin() {
+        let key = KeyExpression::try_from_str(&format!("{XPUB}/<0;1>/*")).unwrap();
+        assert!(!key.has_origin());
+        assert!(key.derivation.is_empty());
+        assert_eq!(key.multipath, Some((0, 1)));
+        assert!(key.wildcard);
+
+        let key = KeyExpression::try_from_str(&format!("{XPUB}/7/<2;3>/*")).unwrap();
+        assert_eq!(key.derivation, DerivationPath::from_str("m/7").unwrap());
+        assert_eq!(key.multipath, Some((2, 3)));
+
+        let invalid = [
+            format!("{XPUB}/<0;1;2>/*"),
+            format!("{XPUB}/<0h;1h>/*"),
+            format!("{XPUB}/<0;1>/<0;1>/*"),
+            format!("{XPUB}/<0;1/*"),
+        ];
+
+        for key in invalid {
+            assert!(
+                matches!(
+                    KeyExpression::try_from_str(&key),
+                    Err(Error::InvalidMultipath(_) | Error::InvalidStep(_))
+                ),
+                "{key}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_private_key_provided() {
+        let xprv = "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi";
+
+        for prefix in PRIVATE_KEY_PREFIXES {
+            let key = format!("[817e7be0/84h/0h/0h]{prefix}{}/<0;1>/*", &xprv[4..]);
+            let error = KeyExpression::try_from_str(&key).unwrap_err();
+
+            assert!(
+                matches!(&error, Error::PrivateKeyProvided(found) if found == prefix),
+                "{key}"
+            );
+            assert!(!error.to_string().contains(&xprv[4..]));
+        }
+
+        let error = KeyExpression::try_from_str(xprv).unwrap_err();
+        assert!(matches!(error, Error::PrivateKeyProvided(_)));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        let invalid = [
+            format!("[817e7be0/84h/0h/0h{XPUB}"),
+            format!("[nothex/84h]{XPUB}"),
+            format!("{XPUB}/0h/*"),
+            format!("{XPUB}/<0;1>/0/*"),
+            format!("{XPUB}/*/0"),
+            format!("{XPUB}/<0,1>/*"),
+        ];
+
+        for key in invalid {
+            assert!(KeyExpression::try_from_str(&key).is_err(), "{key}");
+        }
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let keys = [
+            format!("[817e7be0/84'/0'/0']{XPUB}/<0;1>/*"),
+            format!("[817e7be0]{XPUB}/0/*"),
+            format!("{XPUB}/0/1"),
+            XPUB.to_string(),
+        ];
+
+        for key in keys {
+            let parsed = KeyExpression::try_from_str(&key).unwrap();
+            assert_eq!(parsed.to_string(), key);
+            assert_eq!(
+                KeyExpression::try_from_str(&parsed.to_string()).unwrap(),
+                parsed
+            );
+        }
+
+        let key = KeyExpression::try_from_str(&format!("[817E7BE0/m/84h/0h/0h]{XPUB}/<0;1>/*"));
+        assert_eq!(
+            key.unwrap().to_string(),
+            format!("[817e7be0/84'/0'/0']{XPUB}/<0;1>/*")
+        );
+
+        let mut no_fingerprint =
+            KeyExpression::try_from_str(&format!("[817e7be0/84h]{XPUB}")).unwrap();
+        no_fingerprint.fingerprint = None;
+        assert_eq!(no_fingerprint.to_string(), XPUB);
+    }
+
+    #[test]
+    fn test_to_string_with_marker() {
+        let key =
+            KeyExpression::try_from_str(&format!("[817e7be0/84'/0'/0']{XPUB}/<0;1>/*")).unwrap();
+
+        assert_eq!(
+            key.to_string_with_marker(HardenedMarker::H),
+            format!("[817e7be0/84h/0h/0h]{XPUB}/<0;1>/*")
+        );
+        assert_eq!(
+            key.to_string_with_marker(HardenedMarker::Apostrophe),
+            key.to_string()
+        );
+
+        let parsed = KeyExpression::try_from_str(&key.to_string_with_marker(HardenedMarker::H));
+        assert_eq!(parsed.unwrap(), key);
+
+        let path = DerivationPath::from_str("m/48h/0h/0h/2h/0").unwrap();
+        assert_eq!(HardenedMarker::H.format_path(&path), "48h/0h/0h/2h/0");
+        assert_eq!(
+            HardenedMarker::Apostrophe.format_path(&path),
+            "48'/0'/0'/2'/0"
+        );
+        assert_eq!(
+            HardenedMarker::default().format_path(&DerivationPath::master()),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_path_format() {
+        let path = DerivationPath::from_str("m/84'/0'/0'").unwrap();
+
+        assert_eq!(PathFormat::MASTER_H.format_path(&path), "m/84h/0h/0h");
+        assert_eq!(
+            PathFormat::MASTER_APOSTROPHE.format_path(&path),
+            "m/84'/0'/0'"
+        );
+        assert_eq!(PathFormat::default().format_path(&path), "84h/0h/0h");
+
+        let apostrophe = PathFormat {
+            marker: HardenedMarker::Apostrophe,
+            master_prefix: false,
+        };
+        assert_eq!(apostrophe.format_path(&path), "84'/0'/0'");
+
+        let master = DerivationPath::master();
+        assert_eq!(PathFormat::MASTER_H.format_path(&master), "m");
+        assert_eq!(PathFormat::default().format_path(&master), "");
+    }
+}
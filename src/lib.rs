@@ -6,11 +6,21 @@
 //! - Descriptors
 //! - Electrum
 //! - Wasabi
-//! - JSON
+//! - Sparrow wallet export, single sig and multisig
+//! - JSON, including multi account Coldcard exports
+//! - BitBox02 (BitBoxApp account)
+//! - Ledger Live account
+//! - Bitcoin Core `listdescriptors`
+//! - BIP388 wallet policies
+//! - BSMS (BIP129) descriptor records
+//! - Key expressions, `[817e7be0/84h/0h/0h]xpub.../<0;1>/*`
+//! - Bare account xpub, zpub or ypub (derivation paths are assumed, see `Descriptors::is_synthesized`)
+//! - UR `crypto-account`, `crypto-output` and `crypto-hdkey` QR codes, with the `ur` feature (see `Format::try_from_ur`)
 //!
 //! # Supported descriptors
 //!
 //! - Single Sig
+//! - Multisig, `sortedmulti` and `multi` in `wsh`, `sh(wsh)` and `sh`, and taproot `multi_a` (see `Format::multisig`)
 //!
 //! # Examples
 //!
@@ -70,10 +80,29 @@
 //! let format = format.unwrap();
 //! assert!(matches!(format, Format::Electrum(_)));
 //! ```
+//!
+//! ## Import from a file path
+//!
+//! ```rust
+//! use pubport::Format;
+//!
+//! let format = Format::try_new_from_path("test/data/descriptor.txt");
+//!
+//! assert!(format.is_ok());
+//! assert!(matches!(format.unwrap(), Format::Descriptor(_)));
+//! ```
 
+pub mod bsms;
+pub mod deriver;
 pub mod descriptor;
 pub mod formats;
 pub mod json;
+pub mod key_expression;
+pub mod multisig;
+pub mod script_type;
+#[cfg(feature = "ur")]
+pub mod ur;
+pub mod wallet_policy;
 pub mod xpub;
 
 pub type Format = formats::Format;
@@ -83,5 +112,35 @@ pub fn parse_from_str(string: &str) -> Result<formats::Format, formats::Error> {
     formats::Format::try_new_from_str(string)
 }
 
+/// Parse many inputs, in parallel with the `rayon` feature, the results are in input order
+///
+/// Every thread keeps its own secp context, so a batch doesn't create one per descriptor.
+///
+/// ```rust
+/// let descriptor = std::fs::read_to_string("test/data/descriptor.txt").unwrap();
+/// let electrum = std::fs::read_to_string("test/data/new-electrum.json").unwrap();
+///
+/// let formats = pubport::parse_many(&[&descriptor, "not a wallet", &electrum]);
+///
+/// assert!(matches!(formats[0], Ok(pubport::Format::Descriptor(_))));
+/// assert!(formats[1].is_err());
+/// assert!(matches!(formats[2], Ok(pubport::Format::Electrum(_))));
+/// ```
+pub fn parse_many(inputs: &[&str]) -> Vec<Result<formats::Format, formats::Error>> {
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        inputs
+            .par_iter()
+            .map(|input| parse_from_str(input))
+            .collect()
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    {
+        inputs.iter().map(|input| parse_from_str(input)).collect()
+    }
+}
+
 #[cfg(feature = "uniffi")]
 uniffi::setup_scaffolding!();
@@ -0,0 +1,682 @@
+use std::str::FromStr as _;
+
+use bitcoin::bip32::{ChildNumber, DerivationPath, Fingerprint, Xpub};
+use miniscript::{
+    descriptor::{ShInner, WshInner},
+    Descriptor, DescriptorPublicKey, Miniscript, ScriptContext, Terminal,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    descriptor::{self, electrum_fingerprint, multipath_key, Descriptors},
+    json::{ElectrumMultisigJson, SparrowMultisigJson},
+    xpub::{self, ByteOrder, OriginalFormat},
+};
+
+/// The BIP341 unspendable internal key, `H = lift_x(sha256(G))`, so a taproot multisig can only
+/// be spent with the script path
+pub const NUMS_INTERNAL_KEY: &str =
+    "50929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0";
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Invalid threshold {threshold}, must be between 1 and the number of keys ({keys})")]
+    InvalidThreshold { threshold: usize, keys: usize },
+
+    #[error("Invalid multisig descriptor: {0}")]
+    InvalidDescriptor(#[from] descriptor::Error),
+
+    #[error("Descriptor is not a sortedmulti, multi or multi_a multisig")]
+    NotMultisig,
+
+    #[error("Single pubkey cosigners are not supported, must be an extended key")]
+    SinglePubkeyNotSupported,
+
+    #[error("Invalid cosigner xpub: {0}")]
+    InvalidXpub(#[from] xpub::Error),
+
+    #[error("Invalid cosigner derivation path: {0}")]
+    InvalidDerivationPath(#[from] bitcoin::bip32::Error),
+
+    #[error("Invalid Electrum keystore: {0}")]
+    InvalidKeystore(#[from] serde_json::Error),
+
+    #[error("Invalid Electrum multisig wallet type: {0}")]
+    InvalidWalletType(String),
+
+    #[error("Expected {expected} cosigners, found {found}")]
+    WrongCosignerCount { expected: usize, found: usize },
+
+    #[error("Cosigners use different script types")]
+    MixedScriptTypes,
+}
+
+/// How the multisig script is wrapped
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    derive_more::Display,
+)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum MultisigScriptType {
+    /// BIP48 script type 2, `wsh(...)`
+    P2wsh,
+
+    /// BIP48 script type 1, `sh(wsh(...))`
+    P2shP2wsh,
+
+    /// Legacy P2SH multisig (BIP45), `sh(...)`
+    P2sh,
+
+    /// Taproot script path multisig, `tr(internal,multi_a(...))`
+    P2tr,
+}
+
+/// One of the keys in a multisig wallet, with its origin
+#[derive(Debug, Clone, Serialize, Deserialize, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub struct Cosigner {
+    pub fingerprint: Fingerprint,
+    pub derivation_path: DerivationPath,
+    pub xpub: Xpub,
+
+    /// Name the wallet export gave the cosigner, like `Coldcard`, not part of the descriptor
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Object))]
+pub struct MultisigDescriptors {
+    pub threshold: usize,
+    pub cosigners: Vec<Cosigner>,
+    pub script_type: MultisigScriptType,
+
+    /// `sortedmulti`, when `false` the cosigners are in script order and the order changes
+    /// every address
+    pub sorted: bool,
+    pub descriptors: Descriptors,
+}
+
+impl TryFrom<&DescriptorPublicKey> for Cosigner {
+    type Error = Error;
+
+    fn try_from(key: &DescriptorPublicKey) -> Result<Self, Self::Error> {
+        let xkey = match key {
+            DescriptorPublicKey::XPub(xkey) => xkey,
+            DescriptorPublicKey::MultiXPub(_) | DescriptorPublicKey::Single(_) => {
+                return Err(Error::SinglePubkeyNotSupported)
+            }
+        };
+
+        let (fingerprint, derivation_path) = match &xkey.origin {
+            Some((fingerprint, path)) => (*fingerprint, path.clone()),
+            None => (xkey.xkey.fingerprint(), DerivationPath::master()),
+        };
+
+        Ok(Self {
+            fingerprint,
+            derivation_path,
+            xpub: xkey.xkey,
+            label: None,
+        })
+    }
+}
+
+impl MultisigDescriptors {
+    /// Parse a multipath `sortedmulti` or `multi` descriptor, native (`wsh`), nested (`sh(wsh)`) or legacy
+    /// (`sh`) P2SH, or a taproot descriptor with a single `multi_a` leaf
+    ///
+    /// The cosigners of a taproot multisig are the keys in the `multi_a`, the internal key is not
+    /// included. `sortedmulti_a` is not supported by miniscript yet.
+    pub fn try_from_line(line: &str) -> Result<Self, Error> {
+        Self::try_from_descriptors(Descriptors::try_from_line(line)?)
+    }
+
+    /// Read the threshold and cosigners out of already parsed descriptors, the same scripts as
+    /// [`MultisigDescriptors::try_from_line`] are supported
+    pub fn try_from_descriptors(descriptors: Descriptors) -> Result<Self, Error> {
+        let (script_type, sorted, (threshold, keys)) = match &descriptors.external {
+            Descriptor::Wsh(wsh) => match wsh.as_inner() {
+                WshInner::SortedMulti(multi) => {
+                    (MultisigScriptType::P2wsh, true, (multi.k(), multi.pks()))
+                }
+                WshInner::Ms(ms) => (MultisigScriptType::P2wsh, false, unsorted_multi(ms)?),
+            },
+            Descriptor::Sh(sh) => match sh.as_inner() {
+                ShInner::Wsh(wsh) => match wsh.as_inner() {
+                    WshInner::SortedMulti(multi) => (
+                        MultisigScriptType::P2shP2wsh,
+                        true,
+                        (multi.k(), multi.pks()),
+                    ),
+                    WshInner::Ms(ms) => (MultisigScriptType::P2shP2wsh, false, unsorted_multi(ms)?),
+                },
+                ShInner::SortedMulti(multi) => {
+                    (MultisigScriptType::P2sh, true, (multi.k(), multi.pks()))
+                }
+                ShInner::Ms(ms) => (MultisigScriptType::P2sh, false, unsorted_multi(ms)?),
+                ShInner::Wpkh(_) => return Err(Error::NotMultisig),
+            },
+            Descriptor::Tr(tr) => {
+                let leaves = tr.iter_scripts().collect::<Vec<_>>();
+                match leaves.as_slice() {
+                    [(_, leaf)] => match &leaf.node {
+                        Terminal::MultiA(multi) => {
+                            (MultisigScriptType::P2tr, false, (multi.k(), multi.data()))
+                        }
+                        _ => return Err(Error::NotMultisig),
+                    },
+                    _ => return Err(Error::NotMultisig),
+                }
+            }
+            _ => return Err(Error::NotMultisig),
+        };
+
+        let cosigners = keys
+            .iter()
+            .map(Cosigner::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            threshold,
+            cosigners,
+            script_type,
+            sorted,
+            descriptors,
+        })
+    }
+
+    /// Build a `sortedmulti` wallet from each cosigner's master fingerprint, origin path and xpub
+    ///
+    /// Taproot wallets use `multi_a`, which keeps the keys in the order given, with the
+    /// unspendable [`NUMS_INTERNAL_KEY`] as the internal key.
+    pub fn from_parts(
+        threshold: usize,
+        keys: Vec<(Fingerprint, DerivationPath, Xpub)>,
+        script_type: MultisigScriptType,
+    ) -> Result<Self, Error> {
+        if threshold == 0 || threshold > keys.len() {
+            return Err(Error::InvalidThreshold {
+                threshold,
+                keys: keys.len(),
+            });
+        }
+
+        let cosigners = keys
+            .into_iter()
+            .map(|(fingerprint, derivation_path, xpub)| Cosigner {
+                fingerprint,
+                derivation_path,
+                xpub,
+                label: None,
+            })
+            .collect::<Vec<_>>();
+
+        let keys = cosigners
+            .iter()
+            .map(|cosigner| {
+                multipath_key(
+                    cosigner.fingerprint,
+                    &cosigner.derivation_path,
+                    cosigner.xpub,
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let multi = match script_type {
+            MultisigScriptType::P2tr => format!("multi_a({threshold},{keys})"),
+            _ => format!("sortedmulti({threshold},{keys})"),
+        };
+        let desc = script_type.wrap_with(&multi);
+        let descriptors = Descriptors::try_from_generated_line(&desc)?;
+
+        Ok(Self {
+            threshold,
+            cosigners,
+            sorted: script_type != MultisigScriptType::P2tr,
+            script_type,
+            descriptors,
+        })
+    }
+}
+
+/// Threshold and keys of a `multi`, in the order they are written
+fn unsorted_multi<Ctx: ScriptContext>(
+    ms: &Miniscript<DescriptorPublicKey, Ctx>,
+) -> Result<(usize, &[DescriptorPublicKey]), Error> {
+    match &ms.node {
+        Terminal::Multi(multi) => Ok((multi.k(), multi.data())),
+        _ => Err(Error::NotMultisig),
+    }
+}
+
+impl TryFrom<ElectrumMultisigJson> for MultisigDescriptors {
+    type Error = Error;
+
+    fn try_from(json: ElectrumMultisigJson) -> Result<Self, Self::Error> {
+        if json.use_encryption {
+            return Err(descriptor::Error::EncryptedWalletUnsupported.into());
+        }
+
+        let invalid_wallet_type = || Error::InvalidWalletType(json.wallet_type.clone());
+
+        // `2of3`
+        let (threshold, count) = json
+            .wallet_type
+            .split_once("of")
+            .ok_or_else(invalid_wallet_type)?;
+
+        let threshold = threshold.parse().map_err(|_| invalid_wallet_type())?;
+        let count = count.parse().map_err(|_| invalid_wallet_type())?;
+
+        let keystores = json.keystores()?;
+        if keystores.len() != count {
+            return Err(Error::WrongCosignerCount {
+                expected: count,
+                found: keystores.len(),
+            });
+        }
+
+        let mut script_type = None;
+        let mut keys = Vec::with_capacity(keystores.len());
+
+        for keystore in &keystores {
+            let xpub = xpub::Xpub::try_from(keystore.xpub.as_str())?;
+            let fingerprint = electrum_fingerprint(keystore, &xpub)?;
+            let derivation_path = DerivationPath::from_str(&keystore.derivation)?;
+
+            // electrum uses plain xpubs for legacy p2sh multisig
+            let keystore_script_type = MultisigScriptType::from_slip132(&xpub.original_format())
+                .unwrap_or(MultisigScriptType::P2sh);
+
+            if script_type.is_some_and(|script_type| script_type != keystore_script_type) {
+                return Err(Error::MixedScriptTypes);
+            }
+
+            script_type = Some(keystore_script_type);
+            keys.push((fingerprint, derivation_path, xpub.extended_key()));
+        }
+
+        let script_type = script_type.unwrap_or(MultisigScriptType::P2sh);
+        Self::from_parts(threshold, keys, script_type)
+    }
+}
+
+impl TryFrom<SparrowMultisigJson> for MultisigDescriptors {
+    type Error = Error;
+
+    fn try_from(json: SparrowMultisigJson) -> Result<Self, Self::Error> {
+        let mut multisig = Self::try_from_line(&json.descriptor)?;
+
+        if !json.keystores.is_empty() && json.keystores.len() != multisig.cosigners.len() {
+            return Err(Error::WrongCosignerCount {
+                expected: multisig.cosigners.len(),
+                found: json.keystores.len(),
+            });
+        }
+
+        for (index, cosigner) in multisig.cosigners.iter_mut().enumerate() {
+            let by_fingerprint = json.keystores.iter().find(|keystore| {
+                keystore.key_derivation.as_ref().is_some_and(|derivation| {
+                    xpub::parse_xfp(&derivation.master_fingerprint, ByteOrder::BigEndian).ok()
+                        == Some(cosigner.fingerprint)
+                })
+            });
+
+            let by_position = json
+                .keystores
+                .get(index)
+                .filter(|keystore| keystore.key_derivation.is_none());
+
+            cosigner.label = by_fingerprint
+                .or(by_position)
+                .map(|keystore| keystore.label.clone());
+        }
+
+        Ok(multisig)
+    }
+}
+
+impl MultisigScriptType {
+    /// Script type implied by a SLIP-132 multisig key prefix, `Zpub` and testnet `Vpub` are native
+    /// segwit, `Ypub` and testnet `Upub` are nested segwit
+    pub fn from_slip132(format: &OriginalFormat) -> Option<MultisigScriptType> {
+        match format {
+            OriginalFormat::MultisigZpub | OriginalFormat::MultisigVpub => {
+                Some(MultisigScriptType::P2wsh)
+            }
+            OriginalFormat::MultisigYpub | OriginalFormat::MultisigUpub => {
+                Some(MultisigScriptType::P2shP2wsh)
+            }
+            OriginalFormat::Zpub
+            | OriginalFormat::Ypub
+            | OriginalFormat::Xpub
+            | OriginalFormat::Tpub
+            | OriginalFormat::Vpub
+            | OriginalFormat::Upub => None,
+        }
+    }
+
+    /// Script type from a BIP48 derivation path, `48'/coin_type'/account'/script_type'`, the
+    /// last step is `1'` for nested segwit and `2'` for native segwit
+    ///
+    /// BIP45 legacy P2SH wallets use `45'`
+    pub fn try_from_derivation_path(path: &DerivationPath) -> Option<MultisigScriptType> {
+        let mut path = path.into_iter();
+
+        match path.next()? {
+            ChildNumber::Hardened { index: 45 } => Some(MultisigScriptType::P2sh),
+            ChildNumber::Hardened { index: 48 } => match path.nth(2)? {
+                ChildNumber::Hardened { index: 1 } => Some(MultisigScriptType::P2shP2wsh),
+                ChildNumber::Hardened { index: 2 } => Some(MultisigScriptType::P2wsh),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// The standard mainnet derivation path of a cosigner key for the first account,
+    /// `48h/0h/0h/2h` for P2WSH
+    ///
+    /// `None` for taproot, there is no standard path for taproot multisig yet
+    pub fn account_derivation_path(&self) -> Option<&'static str> {
+        match self {
+            MultisigScriptType::P2wsh => Some("48h/0h/0h/2h"),
+            MultisigScriptType::P2shP2wsh => Some("48h/0h/0h/1h"),
+            MultisigScriptType::P2sh => Some("45h"),
+            MultisigScriptType::P2tr => None,
+        }
+    }
+
+    pub fn wrap_with(&self, multi: &str) -> String {
+        match self {
+            MultisigScriptType::P2wsh => format!("wsh({multi})"),
+            MultisigScriptType::P2shP2wsh => format!("sh(wsh({multi}))"),
+            MultisigScriptType::P2sh => format!("sh({multi})"),
+            MultisigScriptType::P2tr => format!("tr({NUMS_INTERNAL_KEY},{multi})"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::Network;
+
+    use super::*;
+    use crate::descriptor::Keychain;
+
+    fn keys() -> Vec<(Fingerprint, DerivationPath, Xpub)> {
+        let path = DerivationPath::from_str("m/48h/0h/0h/2h").unwrap();
+
+        [
+            ("817e7be0", "xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM"),
+            ("90645a28", "xpub6CCKAvUTNursEnaJ8k1d27LfqEUzeAx2N9wFqYE3W1xh7nqgJEBEbLSSmohwDxzsSvcsYqiQqFzRvta65Njbe5o84bF5YXHFqfSH2Dkhonm"),
+            ("8dfecfc3", "xpub6BoKN14JzSFN1T3cqe9FnrwnXGAsmbgETJyeazoa3F7aMXh4XndvVrJAYyM127FsrH8KFv5XFXDroqXNfZMfsinow7xp93ueYSpnrjBBFs4"),
+        ]
+        .into_iter()
+        .map(|(fingerprint, xpub)| {
+            (
+                Fingerprint::from_str(fingerprint).unwrap(),
+                path.clone(),
+                Xpub::from_str(xpub).unwrap(),
+            )
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_from_parts() {
+        let multisig = MultisigDescriptors::from_parts(2, keys(), MultisigScriptType::P2wsh);
+        assert!(multisig.is_ok());
+
+        let multisig = multisig.unwrap();
+        assert_eq!(multisig.threshold, 2);
+        assert_eq!(multisig.cosigners.len(), 3);
+        assert_eq!(multisig.descriptors.cosigner_count(), 3);
+
+        let external = multisig.descriptors.external.to_string();
+        assert!(external.starts_with("wsh(sortedmulti(2,[817e7be0/48'/0'/0'/2']xpub6CiK"));
+    }
+
+    #[test]
+    fn test_from_parts_nested() {
+        let multisig =
+            MultisigDescriptors::from_parts(2, keys(), MultisigScriptType::P2shP2wsh).unwrap();
+
+        let external = multisig.descriptors.external.to_string();
+        assert!(external.starts_with("sh(wsh(sortedmulti(2,"));
+    }
+
+    fn multi_keys(path: &str) -> String {
+        keys()
+            .into_iter()
+            .map(|(fingerprint, _, xpub)| multipath_key(fingerprint, path, xpub))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    #[test]
+    fn test_try_from_line_script_types() {
+        let native = format!("wsh(sortedmulti(2,{}))", multi_keys("48h/0h/0h/2h"));
+        let nested = format!("sh(wsh(sortedmulti(2,{})))", multi_keys("48h/0h/0h/1h"));
+        let legacy = format!("sh(sortedmulti(2,{}))", multi_keys("45h"));
+
+        for (line, script_type) in [
+            (native, MultisigScriptType::P2wsh),
+            (nested, MultisigScriptType::P2shP2wsh),
+            (legacy, MultisigScriptType::P2sh),
+        ] {
+            let multisig = MultisigDescriptors::try_from_line(&line).unwrap();
+
+            assert_eq!(multisig.script_type, script_type);
+            assert_eq!(multisig.threshold, 2);
+            assert_eq!(multisig.cosigners.len(), 3);
+            assert_eq!(
+                multisig.cosigners[0].fingerprint,
+                Fingerprint::from_str("817e7be0").unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_try_from_line_legacy_matches_from_parts() {
+        let line = format!("sh(sortedmulti(2,{}))", multi_keys("45h"));
+        let parsed = MultisigDescriptors::try_from_line(&line).unwrap();
+
+        let keys = keys()
+            .into_iter()
+            .map(|(fingerprint, _, xpub)| {
+                (
+                    fingerprint,
+                    DerivationPath::from_str("m/45h").unwrap(),
+                    xpub,
+                )
+            })
+            .collect();
+
+        let built = MultisigDescriptors::from_parts(2, keys, MultisigScriptType::P2sh).unwrap();
+        assert_eq!(parsed, built);
+    }
+
+    #[test]
+    fn test_unsorted_multi_keeps_key_order() {
+        let keys = multi_keys("48h/0h/0h/2h");
+        let mut reordered = keys.split(',').collect::<Vec<_>>();
+        reordered.reverse();
+
+        let line = format!("wsh(multi(2,{}))", reordered.join(","));
+        let multisig = MultisigDescriptors::try_from_line(&line).unwrap();
+        assert!(!multisig.sorted);
+
+        let fingerprints = multisig
+            .cosigners
+            .iter()
+            .map(|cosigner| cosigner.fingerprint.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(fingerprints, ["8dfecfc3", "90645a28", "817e7be0"]);
+
+        let canonical = multisig.descriptors.canonical_string();
+        assert!(canonical.starts_with("wsh(multi(2,[8dfecfc3/48'/0'/0'/2']"));
+
+        let reparsed = MultisigDescriptors::try_from_line(&canonical).unwrap();
+        assert_eq!(reparsed, multisig);
+
+        // sorting the keys would give different addresses
+        let sorted = MultisigDescriptors::try_from_line(&format!("wsh(sortedmulti(2,{keys}))"));
+        let sorted = sorted.unwrap();
+        assert!(sorted.sorted);
+        assert_ne!(
+            sorted
+                .descriptors
+                .address_at(Keychain::External, 0, Network::Bitcoin)
+                .unwrap(),
+            multisig
+                .descriptors
+                .address_at(Keychain::External, 0, Network::Bitcoin)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_try_from_line_single_sig() {
+        let line = "wpkh([817e7be0/84h/0h/0h]xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM/<0;1>/*)";
+        let multisig = MultisigDescriptors::try_from_line(line);
+
+        assert!(matches!(multisig, Err(Error::NotMultisig)));
+    }
+
+    #[test]
+    fn test_from_parts_invalid_threshold() {
+        let multisig = MultisigDescriptors::from_parts(4, keys(), MultisigScriptType::P2wsh);
+        assert!(matches!(
+            multisig,
+            Err(Error::InvalidThreshold {
+                threshold: 4,
+                keys: 3
+            })
+        ));
+
+        let multisig = MultisigDescriptors::from_parts(0, keys(), MultisigScriptType::P2wsh);
+        assert!(matches!(multisig, Err(Error::InvalidThreshold { .. })));
+    }
+
+    #[test]
+    fn test_electrum_multisig() {
+        let string = std::fs::read_to_string("test/data/electrum-multisig.json").unwrap();
+        let json = serde_json::from_str::<ElectrumMultisigJson>(&string).unwrap();
+        let multisig = MultisigDescriptors::try_from(json).unwrap();
+
+        let expected = MultisigDescriptors::from_parts(2, keys(), MultisigScriptType::P2wsh);
+        assert_eq!(multisig, expected.unwrap());
+    }
+
+    #[test]
+    fn test_sparrow_multisig() {
+        let string = std::fs::read_to_string("test/data/sparrow-multisig-export.json").unwrap();
+        let json = serde_json::from_str::<SparrowMultisigJson>(&string).unwrap();
+        let multisig = MultisigDescriptors::try_from(json.clone()).unwrap();
+
+        let two_keys = keys().into_iter().take(2).collect();
+        let expected = MultisigDescriptors::from_parts(2, two_keys, MultisigScriptType::P2wsh);
+        assert_eq!(multisig.descriptors, expected.unwrap().descriptors);
+
+        let labels = |multisig: &MultisigDescriptors| {
+            multisig
+                .cosigners
+                .iter()
+                .map(|cosigner| cosigner.label.clone().unwrap_or_default())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(labels(&multisig), ["Coldcard", "Passport"]);
+
+        // without key derivations the labels are in descriptor order
+        let mut positional = json.clone();
+        positional.keystores.reverse();
+        positional
+            .keystores
+            .iter_mut()
+            .for_each(|keystore| keystore.key_derivation = None);
+        let multisig = MultisigDescriptors::try_from(positional).unwrap();
+        assert_eq!(labels(&multisig), ["Passport", "Coldcard"]);
+
+        let mut missing = json;
+        missing.keystores.pop();
+        assert!(matches!(
+            MultisigDescriptors::try_from(missing),
+            Err(Error::WrongCosignerCount {
+                expected: 2,
+                found: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn test_taproot_multi_a() {
+        let built = MultisigDescriptors::from_parts(2, keys(), MultisigScriptType::P2tr).unwrap();
+
+        let external = built.descriptors.external.to_string();
+        assert!(external.starts_with(&format!("tr({NUMS_INTERNAL_KEY},multi_a(2,[817e7be0/")));
+
+        let line = format!("{:#}", built.descriptors.external).replace("/0/*", "/<0;1>/*");
+        let multisig = MultisigDescriptors::try_from_line(&line).unwrap();
+
+        assert_eq!(multisig.script_type, MultisigScriptType::P2tr);
+        assert_eq!(multisig.threshold, 2);
+        assert_eq!(multisig.cosigners, built.cosigners);
+
+        // the unspendable internal key is not a cosigner
+        assert_eq!(built.descriptors.cosigner_count(), 3);
+        assert_eq!(
+            built.descriptors.fingerprints(),
+            keys()
+                .into_iter()
+                .map(|(fingerprint, _, _)| fingerprint)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_script_type_from_derivation_path() {
+        let script_type = |path: &str| {
+            MultisigScriptType::try_from_derivation_path(&DerivationPath::from_str(path).unwrap())
+        };
+
+        assert_eq!(
+            script_type("m/48h/0h/0h/2h"),
+            Some(MultisigScriptType::P2wsh)
+        );
+        assert_eq!(
+            script_type("m/48h/1h/3h/1h"),
+            Some(MultisigScriptType::P2shP2wsh)
+        );
+        assert_eq!(script_type("m/45h"), Some(MultisigScriptType::P2sh));
+        assert_eq!(script_type("m/48h/0h/0h"), None);
+        assert_eq!(script_type("m/48h/0h/0h/3h"), None);
+        assert_eq!(script_type("m/84h/0h/0h"), None);
+
+        for script_type in [
+            MultisigScriptType::P2wsh,
+            MultisigScriptType::P2shP2wsh,
+            MultisigScriptType::P2sh,
+        ] {
+            let path = script_type.account_derivation_path().unwrap();
+            let path = DerivationPath::from_str(&format!("m/{path}")).unwrap();
+            assert_eq!(
+                MultisigScriptType::try_from_derivation_path(&path),
+                Some(script_type)
+            );
+        }
+
+        assert_eq!(MultisigScriptType::P2tr.account_derivation_path(), None);
+    }
+}
@@ -0,0 +1,241 @@
+use bitcoin::bip32::{ChildNumber, DerivationPath};
+use serde::{Deserialize, Serialize};
+
+use crate::xpub::OriginalFormat;
+
+/// Single sig script types, named like the `name` field in the generic JSON export
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    derive_more::Display,
+)]
+#[serde(rename_all = "kebab-case")]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum ScriptType {
+    /// BIP44
+    #[serde(alias = "legacy", alias = "P2PKH")]
+    P2pkh,
+
+    /// BIP49, older Electrum versions and some other tools write it as `p2wpkh-p2sh`, Sparrow as
+    /// `P2SH_P2WPKH`
+    #[serde(
+        alias = "p2wpkh-p2sh",
+        alias = "sh-wpkh",
+        alias = "p2sh-segwit",
+        alias = "P2SH_P2WPKH"
+    )]
+    P2shP2wpkh,
+
+    /// BIP84
+    #[serde(alias = "bech32", alias = "segwit", alias = "P2WPKH")]
+    P2wpkh,
+
+    /// BIP86, single key taproot
+    #[serde(alias = "P2TR")]
+    P2tr,
+}
+
+/// Something suspicious in an export that doesn't stop it from being imported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, thiserror::Error)]
+pub enum Warning {
+    #[error("Key prefix is for {from_prefix}, but the derivation path or declared script type is {from_path}")]
+    ScriptTypeMismatch {
+        from_prefix: ScriptType,
+        from_path: ScriptType,
+    },
+}
+
+impl ScriptType {
+    /// Warns when the SLIP-132 prefix of a key implies a different script type than this one,
+    /// like a `zpub` declared as P2PKH
+    pub fn check_slip132(&self, format: &OriginalFormat) -> Option<Warning> {
+        match Self::from_slip132(format) {
+            Some(from_prefix) if from_prefix != *self => Some(Warning::ScriptTypeMismatch {
+                from_prefix,
+                from_path: *self,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Script type implied by a SLIP-132 key prefix, `zpub` and testnet `vpub` are native segwit,
+    /// `ypub` and testnet `upub` are nested segwit
+    ///
+    /// Returns `None` for a plain `xpub`, it is used for legacy keys but also by most descriptor
+    /// based wallets for every script type, so the prefix alone doesn't say anything
+    pub fn from_slip132(format: &OriginalFormat) -> Option<ScriptType> {
+        match format {
+            OriginalFormat::Zpub | OriginalFormat::Vpub => Some(ScriptType::P2wpkh),
+            OriginalFormat::Ypub | OriginalFormat::Upub => Some(ScriptType::P2shP2wpkh),
+            OriginalFormat::Xpub
+            | OriginalFormat::Tpub
+            | OriginalFormat::MultisigZpub
+            | OriginalFormat::MultisigYpub
+            | OriginalFormat::MultisigVpub
+            | OriginalFormat::MultisigUpub => None,
+        }
+    }
+
+    /// Script type from the BIP purpose (the first element) of a derivation path
+    pub fn try_from_derivation_path(path: &DerivationPath) -> Option<ScriptType> {
+        match path.into_iter().next()? {
+            ChildNumber::Hardened { index: 44 } => Some(ScriptType::P2pkh),
+            ChildNumber::Hardened { index: 49 } => Some(ScriptType::P2shP2wpkh),
+            ChildNumber::Hardened { index: 84 } => Some(ScriptType::P2wpkh),
+            ChildNumber::Hardened { index: 86 } => Some(ScriptType::P2tr),
+            _ => None,
+        }
+    }
+
+    /// The BIP purpose, the first step of the derivation path, `84` for P2WPKH
+    pub fn purpose(&self) -> u32 {
+        match self {
+            ScriptType::P2pkh => 44,
+            ScriptType::P2shP2wpkh => 49,
+            ScriptType::P2wpkh => 84,
+            ScriptType::P2tr => 86,
+        }
+    }
+
+    /// The standard mainnet account derivation path for the first account, `84h/0h/0h` for P2WPKH
+    ///
+    /// Only used to build descriptor strings that are parsed again, miniscript writes every
+    /// parsed descriptor with `'` markers so the marker used here never shows up in the output.
+    pub fn account_derivation_path(&self) -> &'static str {
+        match self {
+            ScriptType::P2pkh => "44h/0h/0h",
+            ScriptType::P2shP2wpkh => "49h/0h/0h",
+            ScriptType::P2wpkh => "84h/0h/0h",
+            ScriptType::P2tr => "86h/0h/0h",
+        }
+    }
+
+    /// Wrap a key expression in the script for this script type, `wpkh(...)` for P2WPKH
+    pub fn wrap_with(&self, script: &str) -> String {
+        match self {
+            ScriptType::P2pkh => format!("pkh({})", script),
+            ScriptType::P2shP2wpkh => format!("sh(wpkh({}))", script),
+            ScriptType::P2wpkh => format!("wpkh({})", script),
+            ScriptType::P2tr => format!("tr({})", script),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr as _;
+
+    use super::*;
+    use crate::xpub::Xpub;
+
+    #[test]
+    fn test_from_slip132() {
+        let zpub = Xpub::try_from("zpub6rNrPrFwgm4wMBSysetK5tpLBS2HYT8TDKQA6amxFHKJUnQq8rNtc4JDfGYPbvF9wJyagPpG1Faqnfe3BB8XzKon8LwW9KkMWyAQ4RQHzB1").unwrap();
+        let ypub = Xpub::try_from("ypub6X2aUb9NXbQM65mQy6oFECSB1CdSanwXHGTUcw7vt2LaAteuYtLoDQ6ao1fXDsenrZjgJKJyHvLypBBeo59cSKUivvwW8S6k7PVvQkVosxZ").unwrap();
+        let xpub = Xpub::try_from("xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM").unwrap();
+
+        assert_eq!(
+            ScriptType::from_slip132(&zpub.original_format()),
+            Some(ScriptType::P2wpkh)
+        );
+
+        assert_eq!(
+            ScriptType::from_slip132(&ypub.original_format()),
+            Some(ScriptType::P2shP2wpkh)
+        );
+
+        assert_eq!(ScriptType::from_slip132(&xpub.original_format()), None);
+
+        assert_eq!(
+            ScriptType::from_slip132(&OriginalFormat::Vpub),
+            Some(ScriptType::P2wpkh)
+        );
+        assert_eq!(
+            ScriptType::from_slip132(&OriginalFormat::Upub),
+            Some(ScriptType::P2shP2wpkh)
+        );
+        assert_eq!(ScriptType::from_slip132(&OriginalFormat::Tpub), None);
+    }
+
+    #[test]
+    fn test_check_slip132() {
+        let zpub = Xpub::try_from("zpub6rNrPrFwgm4wMBSysetK5tpLBS2HYT8TDKQA6amxFHKJUnQq8rNtc4JDfGYPbvF9wJyagPpG1Faqnfe3BB8XzKon8LwW9KkMWyAQ4RQHzB1").unwrap();
+        let xpub = Xpub::try_from("xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM").unwrap();
+
+        assert_eq!(
+            ScriptType::P2wpkh.check_slip132(&zpub.original_format()),
+            None
+        );
+        assert_eq!(
+            ScriptType::P2pkh.check_slip132(&xpub.original_format()),
+            None
+        );
+        assert_eq!(
+            ScriptType::P2pkh.check_slip132(&zpub.original_format()),
+            Some(Warning::ScriptTypeMismatch {
+                from_prefix: ScriptType::P2wpkh,
+                from_path: ScriptType::P2pkh
+            })
+        );
+    }
+
+    #[test]
+    fn test_serde_aliases() {
+        let parse =
+            |name: &str| serde_json::from_str::<ScriptType>(&format!("\"{name}\"")).unwrap();
+
+        assert_eq!(parse("p2pkh"), ScriptType::P2pkh);
+        assert_eq!(parse("legacy"), ScriptType::P2pkh);
+
+        assert_eq!(parse("p2sh-p2wpkh"), ScriptType::P2shP2wpkh);
+        assert_eq!(parse("p2wpkh-p2sh"), ScriptType::P2shP2wpkh);
+        assert_eq!(parse("sh-wpkh"), ScriptType::P2shP2wpkh);
+        assert_eq!(parse("p2sh-segwit"), ScriptType::P2shP2wpkh);
+
+        assert_eq!(parse("p2wpkh"), ScriptType::P2wpkh);
+        assert_eq!(parse("bech32"), ScriptType::P2wpkh);
+        assert_eq!(parse("segwit"), ScriptType::P2wpkh);
+
+        // always written with the canonical name
+        let written = serde_json::to_string(&ScriptType::P2shP2wpkh).unwrap();
+        assert_eq!(written, "\"p2sh-p2wpkh\"");
+    }
+
+    #[test]
+    fn test_try_from_derivation_path() {
+        let path = |path: &str| DerivationPath::from_str(path).unwrap();
+
+        assert_eq!(
+            ScriptType::try_from_derivation_path(&path("m/84h/0h/0h")),
+            Some(ScriptType::P2wpkh)
+        );
+
+        assert_eq!(
+            ScriptType::try_from_derivation_path(&path("m/49'/0'/0'")),
+            Some(ScriptType::P2shP2wpkh)
+        );
+
+        assert_eq!(
+            ScriptType::try_from_derivation_path(&path("m/44h/0h/0h")),
+            Some(ScriptType::P2pkh)
+        );
+
+        assert_eq!(
+            ScriptType::try_from_derivation_path(&path("m/86h/0h/0h")),
+            Some(ScriptType::P2tr)
+        );
+
+        assert_eq!(
+            ScriptType::try_from_derivation_path(&path("m/84/0h/0h")),
+            None
+        );
+    }
+}
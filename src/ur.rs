@@ -0,0 +1,865 @@
+//! Uniform Resources (UR), the QR code encoding airgapped signers like Keystone, Passport and
+//! SeedSigner export their keys in, see BCR-2020-005
+//!
+//! Supports `crypto-account`, `crypto-output` and `crypto-hdkey`. A multi-part UR is assembled
+//! from its first `seqLen` parts, the fountain encoded parts after those mix several fragments
+//! together and are skipped.
+
+use std::collections::BTreeMap;
+
+use bitcoin::{
+    bip32::{ChainCode, ChildNumber, DerivationPath, Fingerprint, Xpub},
+    secp256k1::PublicKey,
+    NetworkKind,
+};
+
+use crate::key_expression::PathFormat;
+
+/// The bytewords, a UR writes each byte as the first and last letter of its word
+const BYTEWORDS: [&str; 256] = [
+    "able", "acid", "also", "apex", "aqua", "arch", "atom", "aunt", "away", "axis", "back", "bald",
+    "barn", "belt", "beta", "bias", "blue", "body", "brag", "brew", "bulb", "buzz", "calm", "cash",
+    "cats", "chef", "city", "claw", "code", "cola", "cook", "cost", "crux", "curl", "cusp", "cyan",
+    "dark", "data", "days", "deli", "dice", "diet", "door", "down", "draw", "drop", "drum", "dull",
+    "duty", "each", "easy", "echo", "edge", "epic", "even", "exam", "exit", "eyes", "fact", "fair",
+    "fern", "figs", "film", "fish", "fizz", "flap", "flew", "flux", "foxy", "free", "frog", "fuel",
+    "fund", "gala", "game", "gear", "gems", "gift", "girl", "glow", "good", "gray", "grim", "guru",
+    "gush", "gyro", "half", "hang", "hard", "hawk", "heat", "help", "high", "hill", "holy", "hope",
+    "horn", "huts", "iced", "idea", "idle", "inch", "inky", "into", "iris", "iron", "item", "jade",
+    "jazz", "join", "jolt", "jowl", "judo", "jugs", "jump", "junk", "jury", "keep", "keno", "kept",
+    "keys", "kick", "kiln", "king", "kite", "kiwi", "knob", "lamb", "lava", "lazy", "leaf", "legs",
+    "liar", "limp", "lion", "list", "logo", "loud", "love", "luau", "luck", "lung", "main", "many",
+    "math", "maze", "memo", "menu", "meow", "mild", "mint", "miss", "monk", "nail", "navy", "need",
+    "news", "next", "noon", "note", "numb", "obey", "oboe", "omit", "onyx", "open", "oval", "owls",
+    "paid", "part", "peck", "play", "plus", "poem", "pool", "pose", "puff", "puma", "purr", "quad",
+    "quiz", "race", "ramp", "real", "redo", "rich", "road", "rock", "roof", "ruby", "ruin", "runs",
+    "rust", "safe", "saga", "scar", "sets", "silk", "skew", "slot", "soap", "solo", "song", "stub",
+    "surf", "swan", "taco", "task", "taxi", "tent", "tied", "time", "tiny", "toil", "tomb", "toys",
+    "trip", "tuna", "twin", "ugly", "undo", "unit", "urge", "user", "vast", "very", "veto", "vial",
+    "vibe", "view", "visa", "void", "vows", "wall", "wand", "warm", "wasp", "wave", "waxy", "webs",
+    "what", "when", "whiz", "wolf", "work", "yank", "yawn", "yell", "yoga", "yurt", "zaps", "zero",
+    "zest", "zinc", "zone", "zoom",
+];
+
+// CBOR tags from BCR-2020-007 and BCR-2020-010
+const TAG_HDKEY: u64 = 303;
+const TAG_KEYPATH: u64 = 304;
+const TAG_COIN_INFO: u64 = 305;
+const TAG_OUTPUT: u64 = 308;
+const TAG_SH: u64 = 400;
+const TAG_WSH: u64 = 401;
+const TAG_PKH: u64 = 403;
+const TAG_WPKH: u64 = 404;
+const TAG_MULTI: u64 = 406;
+const TAG_SORTED_MULTI: u64 = 407;
+const TAG_TR: u64 = 409;
+
+/// Deeper CBOR than any key export needs, so a hostile QR code can't overflow the stack
+const MAX_CBOR_DEPTH: usize = 16;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Not a UR, expected `ur:<type>/<data>`")]
+    NotUr,
+
+    #[error("No UR parts")]
+    NoParts,
+
+    #[error("Unsupported UR type {0}, expected crypto-account, crypto-output or crypto-hdkey")]
+    UnsupportedType(String),
+
+    #[error("UR parts are from different QR codes")]
+    MixedParts,
+
+    #[error("Missing UR part {missing} of {total}, scan every part")]
+    MissingPart { missing: usize, total: usize },
+
+    #[error("Invalid bytewords: {0}")]
+    InvalidBytewords(String),
+
+    #[error("UR checksum doesn't match, the QR code was misread")]
+    InvalidChecksum,
+
+    #[error("Invalid CBOR in UR: {0}")]
+    InvalidCbor(&'static str),
+
+    #[error("UR has a private key, export the public key instead")]
+    PrivateKey,
+
+    #[error("Unsupported script in UR output descriptor, CBOR tag {0}")]
+    UnsupportedScript(u64),
+
+    #[error("UR account has no single sig outputs")]
+    NoSingleSigOutputs,
+
+    #[error("Invalid public key in UR: {0}")]
+    InvalidKey(#[from] bitcoin::secp256k1::Error),
+}
+
+/// What a UR decodes to, as descriptor text for the rest of the parsing to pick up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Payload {
+    /// `crypto-account`, one descriptor per script type
+    Account(Vec<String>),
+
+    /// `crypto-output`, a single descriptor
+    Output(String),
+
+    /// `crypto-hdkey`, a key expression or a bare xpub when it has no origin
+    HdKey(String),
+}
+
+/// Assemble the UR parts and decode the payload, a single part UR is a slice of one
+pub(crate) fn decode(parts: &[&str]) -> Result<Payload, Error> {
+    let (ur_type, message) = assemble(parts)?;
+    let cbor = Reader::new(&message).value(0)?;
+
+    match ur_type.as_str() {
+        "crypto-account" => account(&cbor).map(Payload::Account),
+        "crypto-output" => script(&cbor, None).map(Payload::Output),
+        "crypto-hdkey" => key_expression(&cbor, None).map(Payload::HdKey),
+        _ => Err(Error::UnsupportedType(ur_type)),
+    }
+}
+
+/// The UR type and the message, joined from the fragments of a multi-part UR
+fn assemble(parts: &[&str]) -> Result<(String, Vec<u8>), Error> {
+    let mut ur_type = None;
+    let mut sequence = None;
+    let mut fragments = BTreeMap::new();
+
+    for part in parts {
+        let part = part.trim().to_ascii_lowercase();
+        let body = part.strip_prefix("ur:").ok_or(Error::NotUr)?;
+
+        let (part_type, body) = body.split_once('/').ok_or(Error::NotUr)?;
+        if ur_type.get_or_insert_with(|| part_type.to_string()) != part_type {
+            return Err(Error::MixedParts);
+        }
+
+        // a single part UR has no `seqNum-seqLen` before the data
+        let Some((_, body)) = body.split_once('/') else {
+            if parts.len() != 1 {
+                return Err(Error::MixedParts);
+            }
+
+            return Ok((part_type.to_string(), bytewords(body)?));
+        };
+
+        let bytes = bytewords(body)?;
+        let (index, header, fragment) = fragment(&Reader::new(&bytes).value(0)?)?;
+        if *sequence.get_or_insert(header) != header {
+            return Err(Error::MixedParts);
+        }
+
+        // parts past `seqLen` xor several fragments together, the pure ones are enough
+        if index <= header.count {
+            fragments.insert(index, fragment);
+        }
+    }
+
+    let (Some(ur_type), Some(sequence)) = (ur_type, sequence) else {
+        return Err(Error::NoParts);
+    };
+
+    let mut message = Vec::new();
+    for index in 1..=sequence.count {
+        let fragment = fragments.remove(&index).ok_or(Error::MissingPart {
+            missing: index,
+            total: sequence.count,
+        })?;
+        message.extend(fragment);
+    }
+
+    if message.len() < sequence.length {
+        return Err(Error::InvalidCbor("fragments are shorter than the message"));
+    }
+
+    message.truncate(sequence.length);
+    if crc32(&message) != sequence.checksum {
+        return Err(Error::InvalidChecksum);
+    }
+
+    Ok((ur_type, message))
+}
+
+/// What every part of a multi-part UR shares
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Sequence {
+    count: usize,
+    length: usize,
+    checksum: u32,
+}
+
+/// `[seqNum, seqLen, messageLen, checksum, data]`
+fn fragment(cbor: &Cbor) -> Result<(usize, Sequence, Vec<u8>), Error> {
+    let invalid = || Error::InvalidCbor("expected a multi-part fragment");
+
+    let [index, count, length, checksum, Cbor::Bytes(data)] =
+        cbor.as_array().ok_or_else(invalid)?
+    else {
+        return Err(invalid());
+    };
+
+    let index = index.as_uint().ok_or_else(invalid)? as usize;
+    let sequence = Sequence {
+        count: count.as_uint().ok_or_else(invalid)? as usize,
+        length: length.as_uint().ok_or_else(invalid)? as usize,
+        checksum: checksum.as_u32().ok_or_else(invalid)?,
+    };
+
+    if index == 0 || sequence.count == 0 {
+        return Err(invalid());
+    }
+
+    Ok((index, sequence, data.clone()))
+}
+
+/// Decode minimal bytewords, two letters a byte, and check the CRC32 at the end
+fn bytewords(string: &str) -> Result<Vec<u8>, Error> {
+    let invalid = || Error::InvalidBytewords(string.to_string());
+    if !string.is_ascii() || !string.len().is_multiple_of(2) {
+        return Err(invalid());
+    }
+
+    let mut bytes = string
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            BYTEWORDS
+                .iter()
+                .position(|word| {
+                    let word = word.as_bytes();
+                    word[0] == pair[0] && word[3] == pair[1]
+                })
+                .map(|index| index as u8)
+                .ok_or_else(invalid)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if bytes.len() < 5 {
+        return Err(invalid());
+    }
+
+    let checksum = bytes.split_off(bytes.len() - 4);
+    if crc32(&bytes).to_be_bytes()[..] != checksum[..] {
+        return Err(Error::InvalidChecksum);
+    }
+
+    Ok(bytes)
+}
+
+/// CRC-32 as used by bytewords and the multi-part checksum, the same one as zip and PNG
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    !crc
+}
+
+/// `{1: master-fingerprint, 2: [+ output-descriptor]}`
+fn account(cbor: &Cbor) -> Result<Vec<String>, Error> {
+    let fingerprint = cbor
+        .get(1)
+        .and_then(Cbor::as_u32)
+        .ok_or(Error::InvalidCbor("account has no master fingerprint"))?;
+    let fingerprint = Fingerprint::from(fingerprint.to_be_bytes());
+
+    let outputs = cbor
+        .get(2)
+        .and_then(Cbor::as_array)
+        .ok_or(Error::InvalidCbor("account has no output descriptors"))?;
+
+    let mut descriptors = Vec::new();
+    for output in outputs {
+        match script(output, Some(fingerprint)) {
+            Ok(descriptor) => descriptors.push(descriptor),
+            // accounts can list scripts the rest of pubport doesn't handle, like bare `wsh(key)`
+            Err(Error::UnsupportedScript(_)) => continue,
+            Err(error) => return Err(error),
+        }
+    }
+
+    Ok(descriptors)
+}
+
+/// The descriptor for a tagged script expression
+fn script(cbor: &Cbor, fingerprint: Option<Fingerprint>) -> Result<String, Error> {
+    let Cbor::Tag(tag, inner) = cbor else {
+        return Err(Error::InvalidCbor("expected a tagged script expression"));
+    };
+
+    let key = || derived_key(inner, fingerprint);
+
+    let descriptor = match *tag {
+        TAG_OUTPUT => script(inner, fingerprint)?,
+        TAG_SH => format!("sh({})", script(inner, fingerprint)?),
+        TAG_WSH if matches!(**inner, Cbor::Tag(TAG_HDKEY, _)) => {
+            return Err(Error::UnsupportedScript(TAG_WSH))
+        }
+        TAG_WSH => format!("wsh({})", script(inner, fingerprint)?),
+        TAG_PKH => format!("pkh({})", key()?),
+        TAG_WPKH => format!("wpkh({})", key()?),
+        TAG_TR => format!("tr({})", key()?),
+        TAG_MULTI | TAG_SORTED_MULTI => {
+            let name = if *tag == TAG_MULTI {
+                "multi"
+            } else {
+                "sortedmulti"
+            };
+            let threshold = inner
+                .get(1)
+                .and_then(Cbor::as_uint)
+                .ok_or(Error::InvalidCbor("multisig has no threshold"))?;
+            let keys = inner
+                .get(2)
+                .and_then(Cbor::as_array)
+                .ok_or(Error::InvalidCbor("multisig has no keys"))?
+                .iter()
+                .map(|key| derived_key(key, fingerprint))
+                .collect::<Result<Vec<_>, Error>>()?;
+
+            format!("{name}({threshold},{})", keys.join(","))
+        }
+        tag => return Err(Error::UnsupportedScript(tag)),
+    };
+
+    Ok(descriptor)
+}
+
+/// The key of a script with the derivation from its `children` keypath, `xpub/0/*`, or both
+/// keychains `xpub/<0;1>/*` when it has none
+fn derived_key(cbor: &Cbor, fingerprint: Option<Fingerprint>) -> Result<String, Error> {
+    let key = key_expression(cbor, fingerprint)?;
+
+    let children = match cbor {
+        Cbor::Tag(_, hdkey) => tagged(hdkey.get(7), TAG_KEYPATH),
+        _ => None,
+    };
+
+    match children {
+        Some(children) => Ok(format!("{key}/{}", children_path(children)?)),
+        None => Ok(format!("{key}/<0;1>/*")),
+    }
+}
+
+/// `[fingerprint/origin]xpub` for a `crypto-hdkey`, the origin is left out when no master
+/// fingerprint is known
+///
+/// `fingerprint` is the account's, used when the key origin doesn't have its own.
+fn key_expression(cbor: &Cbor, fingerprint: Option<Fingerprint>) -> Result<String, Error> {
+    let Cbor::Tag(TAG_HDKEY, hdkey) = cbor else {
+        return Err(Error::InvalidCbor("expected a crypto-hdkey"));
+    };
+
+    if hdkey.get(2).and_then(Cbor::as_bool) == Some(true) {
+        return Err(Error::PrivateKey);
+    }
+
+    let key_data = hdkey
+        .get(3)
+        .and_then(Cbor::as_bytes)
+        .ok_or(Error::InvalidCbor("hdkey has no key data"))?;
+    if key_data.first() == Some(&0) {
+        return Err(Error::PrivateKey);
+    }
+
+    let chain_code: [u8; 32] = hdkey
+        .get(4)
+        .and_then(Cbor::as_bytes)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(Error::InvalidCbor("hdkey has no chain code"))?;
+
+    // `{1: type, 2: network}`, mainnet when left out
+    let network = match tagged(hdkey.get(5), TAG_COIN_INFO).and_then(|info| info.get(2)) {
+        Some(network) if network.as_uint() == Some(1) => NetworkKind::Test,
+        _ => NetworkKind::Main,
+    };
+
+    // `{1: components, 2: source-fingerprint, 3: depth}`
+    let origin = tagged(hdkey.get(6), TAG_KEYPATH);
+    let path = match origin {
+        Some(origin) => derivation_path(origin)?,
+        None => DerivationPath::master(),
+    };
+
+    let depth = origin
+        .and_then(|origin| origin.get(3))
+        .and_then(Cbor::as_uint)
+        .unwrap_or(path.len() as u64);
+
+    let xpub = Xpub {
+        network,
+        depth: u8::try_from(depth).map_err(|_| Error::InvalidCbor("hdkey depth is too big"))?,
+        parent_fingerprint: hdkey
+            .get(8)
+            .and_then(Cbor::as_u32)
+            .map(|fingerprint| Fingerprint::from(fingerprint.to_be_bytes()))
+            .unwrap_or_default(),
+        child_number: path
+            .into_iter()
+            .last()
+            .copied()
+            .unwrap_or(ChildNumber::Normal { index: 0 }),
+        public_key: PublicKey::from_slice(key_data)?,
+        chain_code: ChainCode::from(chain_code),
+    };
+
+    let source_fingerprint = origin
+        .and_then(|origin| origin.get(2))
+        .and_then(Cbor::as_u32)
+        .map(|fingerprint| Fingerprint::from(fingerprint.to_be_bytes()))
+        .or(fingerprint);
+
+    match (origin, source_fingerprint) {
+        (Some(_), Some(fingerprint)) if path.is_empty() => Ok(format!("[{fingerprint}]{xpub}")),
+        (Some(_), Some(fingerprint)) => {
+            let path = PathFormat::default().format_path(&path);
+            Ok(format!("[{fingerprint}/{path}]{xpub}"))
+        }
+        _ => Ok(xpub.to_string()),
+    }
+}
+
+/// The origin path from the `[index, hardened, ...]` components of a keypath
+fn derivation_path(keypath: &Cbor) -> Result<DerivationPath, Error> {
+    let invalid = || Error::InvalidCbor("invalid keypath components");
+
+    let components = keypath
+        .get(1)
+        .and_then(Cbor::as_array)
+        .ok_or_else(invalid)?;
+    if !components.len().is_multiple_of(2) {
+        return Err(invalid());
+    }
+
+    components
+        .chunks(2)
+        .map(|component| {
+            let index = component[0].as_u32().ok_or_else(invalid)?;
+            let child = match component[1].as_bool().ok_or_else(invalid)? {
+                true => ChildNumber::from_hardened_idx(index),
+                false => ChildNumber::from_normal_idx(index),
+            };
+
+            child.map_err(|_| invalid())
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(DerivationPath::from)
+}
+
+/// The steps after the key from a `children` keypath, `0/*`
+///
+/// Besides `[index, hardened]` components it can have a `[]` wildcard instead of the index, and
+/// a `[0, false, 1, false]` pair on its own for the `<0;1>` keychains. Ranges of indexes have no
+/// descriptor form.
+fn children_path(keypath: &Cbor) -> Result<String, Error> {
+    let invalid = || Error::InvalidCbor("invalid children keypath components");
+    let mut components = keypath
+        .get(1)
+        .and_then(Cbor::as_array)
+        .ok_or_else(invalid)?
+        .iter();
+
+    let mut steps = Vec::new();
+    while let Some(component) = components.next() {
+        let step = match component {
+            Cbor::Array(pair) if pair.len() == 4 => format!(
+                "<{};{}>",
+                child_step(&pair[0], &pair[1])?,
+                child_step(&pair[2], &pair[3])?
+            ),
+            index => child_step(index, components.next().ok_or_else(invalid)?)?,
+        };
+
+        steps.push(step);
+    }
+
+    if steps.is_empty() {
+        return Err(invalid());
+    }
+
+    Ok(steps.join("/"))
+}
+
+/// One step of a `children` keypath, an index or the `[]` wildcard, `h` if it's hardened
+fn child_step(index: &Cbor, hardened: &Cbor) -> Result<String, Error> {
+    let invalid = || Error::InvalidCbor("invalid children keypath components");
+
+    let index = match index {
+        Cbor::Array(wildcard) if wildcard.is_empty() => "*".to_string(),
+        Cbor::Array(_) => {
+            return Err(Error::InvalidCbor(
+                "children keypath ranges aren't supported",
+            ))
+        }
+        index => index
+            .as_u32()
+            .filter(|index| ChildNumber::from_normal_idx(*index).is_ok())
+            .ok_or_else(invalid)?
+            .to_string(),
+    };
+
+    match hardened.as_bool().ok_or_else(invalid)? {
+        true => Ok(format!("{index}h")),
+        false => Ok(index),
+    }
+}
+
+/// The value inside `tag`, `None` if it's missing or has another tag
+fn tagged(cbor: Option<&Cbor>, tag: u64) -> Option<&Cbor> {
+    match cbor? {
+        Cbor::Tag(found, inner) if *found == tag => Some(inner),
+        _ => None,
+    }
+}
+
+/// The CBOR data items a UR key export uses, floats and simple values are read but not kept
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Cbor {
+    Uint(u64),
+    Bytes(Vec<u8>),
+    Array(Vec<Cbor>),
+    Map(Vec<(Cbor, Cbor)>),
+    Tag(u64, Box<Cbor>),
+    Bool(bool),
+    Other,
+}
+
+impl Cbor {
+    /// The value for an unsigned integer key, the only kind of key URs use
+    fn get(&self, key: u64) -> Option<&Cbor> {
+        let Cbor::Map(entries) = self else {
+            return None;
+        };
+
+        entries
+            .iter()
+            .find(|(found, _)| *found == Cbor::Uint(key))
+            .map(|(_, value)| value)
+    }
+
+    fn as_uint(&self) -> Option<u64> {
+        match self {
+            Cbor::Uint(uint) => Some(*uint),
+            _ => None,
+        }
+    }
+
+    fn as_u32(&self) -> Option<u32> {
+        self.as_uint().and_then(|uint| u32::try_from(uint).ok())
+    }
+
+    fn as_bool(&self) -> Option<bool> {
+        match self {
+            Cbor::Bool(bool) => Some(*bool),
+            _ => None,
+        }
+    }
+
+    fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Cbor::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    fn as_array(&self) -> Option<&[Cbor]> {
+        match self {
+            Cbor::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn take(&mut self, length: u64) -> Result<&'a [u8], Error> {
+        let length = usize::try_from(length).map_err(|_| Error::InvalidCbor("too long"))?;
+        if length > self.bytes.len() {
+            return Err(Error::InvalidCbor("unexpected end"));
+        }
+
+        let (taken, rest) = self.bytes.split_at(length);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    /// The integer after the initial byte, a length, value or tag depending on the major type
+    fn argument(&mut self, info: u8) -> Result<u64, Error> {
+        let size = match info {
+            0..=23 => return Ok(u64::from(info)),
+            24 => 1,
+            25 => 2,
+            26 => 4,
+            27 => 8,
+            _ => return Err(Error::InvalidCbor("indefinite lengths aren't supported")),
+        };
+
+        let mut bytes = [0; 8];
+        bytes[8 - size..].copy_from_slice(self.take(size as u64)?);
+        Ok(u64::from_be_bytes(bytes))
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Cbor, Error> {
+        if depth > MAX_CBOR_DEPTH {
+            return Err(Error::InvalidCbor("nested too deep"));
+        }
+
+        let initial = self.take(1)?[0];
+        let (major, info) = (initial >> 5, initial & 0x1f);
+
+        // simple values and floats, only the booleans are used
+        if major == 7 {
+            return match info {
+                20 => Ok(Cbor::Bool(false)),
+                21 => Ok(Cbor::Bool(true)),
+                _ => self.argument(info).map(|_| Cbor::Other),
+            };
+        }
+
+        let argument = self.argument(info)?;
+        let value = match major {
+            0 => Cbor::Uint(argument),
+            1 => Cbor::Other,
+            2 => Cbor::Bytes(self.take(argument)?.to_vec()),
+            // text, like a key's name, isn't needed for the descriptors
+            3 => {
+                self.take(argument)?;
+                Cbor::Other
+            }
+            4 => Cbor::Array(
+                (0..argument)
+                    .map(|_| self.value(depth + 1))
+                    .collect::<Result<_, _>>()?,
+            ),
+            5 => Cbor::Map(
+                (0..argument)
+                    .map(|_| Ok((self.value(depth + 1)?, self.value(depth + 1)?)))
+                    .collect::<Result<_, Error>>()?,
+            ),
+            _ => Cbor::Tag(argument, Box::new(self.value(depth + 1)?)),
+        };
+
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr as _;
+
+    use bitcoin::Network;
+
+    use super::*;
+    use crate::{
+        descriptor::{Descriptors, Keychain},
+        Format,
+    };
+
+    fn fixture(name: &str) -> String {
+        std::fs::read_to_string(format!("test/data/{name}")).unwrap()
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b"Hello, world!"), 0xebe6c6e6);
+    }
+
+    #[test]
+    fn test_crypto_account() {
+        let ur = fixture("ur-crypto-account.txt");
+        let Format::Json(json) = Format::try_from_ur(&[&ur]).unwrap() else {
+            panic!("expected a JSON format");
+        };
+
+        let first_address = |desc: Option<Descriptors>| {
+            desc.unwrap()
+                .address_at(Keychain::External, 0, Network::Testnet)
+                .unwrap()
+                .to_string()
+        };
+
+        assert_eq!(
+            first_address(json.bip44),
+            "mkpZhYtJu2r87Js3pDiWJDmPte2NRZ8bJV"
+        );
+        assert_eq!(
+            first_address(json.bip49),
+            "2Mww8dCYPUpKHofjgcXcBCEGmniw9CoaiD2"
+        );
+        assert_eq!(
+            first_address(json.bip84),
+            "tb1q6rz28mcfaxtmd6v789l9rrlrusdprr9pqcpvkl"
+        );
+        assert!(json.bip86.is_none());
+
+        // pasted as text it goes through the usual cascade
+        assert!(matches!(Format::try_new_from_str(&ur), Ok(Format::Json(_))));
+    }
+
+    #[test]
+    fn test_multi_part() {
+        let parts = fixture("ur-crypto-account-parts.txt");
+        let mut parts = parts.lines().collect::<Vec<_>>();
+
+        let single = Format::try_from_ur(&[fixture("ur-crypto-account.txt").trim()]).unwrap();
+
+        // pasted as text, one part on each line
+        assert_eq!(Format::try_new_from_str(&parts.join("\n")).unwrap(), single);
+
+        parts.reverse();
+        assert_eq!(Format::try_from_ur(&parts).unwrap(), single);
+
+        parts.remove(1);
+        assert!(matches!(
+            Format::try_from_ur(&parts),
+            Err(crate::Error::InvalidUr(Error::MissingPart {
+                missing: 2,
+                total: 3
+            }))
+        ));
+
+        let output = fixture("ur-crypto-output.txt");
+        parts.push(output.trim());
+        assert!(matches!(decode(&parts), Err(Error::MixedParts)));
+    }
+
+    #[test]
+    fn test_crypto_output() {
+        let ur = fixture("ur-crypto-output.txt");
+        let Payload::Output(descriptor) = decode(&[&ur]).unwrap() else {
+            panic!("expected an output");
+        };
+
+        assert_eq!(
+            descriptor,
+            "wpkh([73c5da0a/84h/1h/0h]tpubDC8msFGeGuwnKG9Upg7DM2b4DaRqg3CUZa5g8v2SRQ6K4NSkxUgd7HsL2XVWbVm39yBA4LAxysQAm397zwQSQoQgewGiYZqrA9DsP4zbQ1M/<0;1>/*)"
+        );
+        assert!(matches!(
+            Format::try_from_ur(&[&ur]),
+            Ok(Format::Descriptor(_))
+        ));
+    }
+
+    #[test]
+    fn test_children_keypath() {
+        let xpub = Xpub::from_str("xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM").unwrap();
+        let keypath = |components: Vec<Cbor>| {
+            Cbor::Tag(
+                TAG_KEYPATH,
+                Box::new(Cbor::Map(vec![(Cbor::Uint(1), Cbor::Array(components))])),
+            )
+        };
+
+        let wpkh = |children: Option<Cbor>| {
+            let mut hdkey = vec![
+                (
+                    Cbor::Uint(3),
+                    Cbor::Bytes(xpub.public_key.serialize().to_vec()),
+                ),
+                (
+                    Cbor::Uint(4),
+                    Cbor::Bytes(xpub.chain_code.to_bytes().to_vec()),
+                ),
+                (
+                    Cbor::Uint(6),
+                    keypath(
+                        [84, 0, 0]
+                            .into_iter()
+                            .flat_map(|index| [Cbor::Uint(index), Cbor::Bool(true)])
+                            .collect(),
+                    ),
+                ),
+                (
+                    Cbor::Uint(8),
+                    Cbor::Uint(u32::from_be_bytes(xpub.parent_fingerprint.to_bytes()).into()),
+                ),
+            ];
+            hdkey.extend(children.map(|children| (Cbor::Uint(7), children)));
+
+            let hdkey = Cbor::Tag(TAG_HDKEY, Box::new(Cbor::Map(hdkey)));
+            script(
+                &Cbor::Tag(TAG_WPKH, Box::new(hdkey)),
+                Some(Fingerprint::from_str("817e7be0").unwrap()),
+            )
+        };
+
+        let origin = format!("[817e7be0/84h/0h/0h]{xpub}");
+        assert_eq!(wpkh(None).unwrap(), format!("wpkh({origin}/<0;1>/*)"));
+
+        let receive = keypath(vec![
+            Cbor::Uint(0),
+            Cbor::Bool(false),
+            Cbor::Array(vec![]),
+            Cbor::Bool(false),
+        ]);
+        assert_eq!(wpkh(Some(receive)).unwrap(), format!("wpkh({origin}/0/*)"));
+
+        let pair = Cbor::Array(vec![
+            Cbor::Uint(0),
+            Cbor::Bool(false),
+            Cbor::Uint(1),
+            Cbor::Bool(false),
+        ]);
+        let keychains = keypath(vec![pair, Cbor::Array(vec![]), Cbor::Bool(false)]);
+        let desc = wpkh(Some(keychains)).unwrap();
+        assert_eq!(desc, format!("wpkh({origin}/<0;1>/*)"));
+        assert!(Descriptors::try_from_line(&desc).is_ok());
+
+        let range = keypath(vec![
+            Cbor::Array(vec![Cbor::Uint(0), Cbor::Uint(9)]),
+            Cbor::Bool(false),
+        ]);
+        assert!(matches!(wpkh(Some(range)), Err(Error::InvalidCbor(_))));
+    }
+
+    #[test]
+    fn test_invalid_ur() {
+        let ur = fixture("ur-crypto-output.txt");
+        let ur = ur.trim();
+
+        // flip the last letter of the checksum
+        let misread = format!("{}{}", &ur[..ur.len() - 1], "a");
+        assert!(matches!(decode(&[&misread]), Err(Error::InvalidChecksum)));
+
+        let unknown = ur.replace("crypto-output", "crypto-psbt");
+        assert!(matches!(
+            decode(&[&unknown]),
+            Err(Error::UnsupportedType(_))
+        ));
+
+        assert!(matches!(decode(&["crypto-output/taad"]), Err(Error::NotUr)));
+        assert!(matches!(decode(&[]), Err(Error::NoParts)));
+        assert!(matches!(
+            decode(&["ur:crypto-output/xxxx"]),
+            Err(Error::InvalidBytewords(_))
+        ));
+    }
+
+    #[test]
+    fn test_cbor_depth() {
+        // an array in an array in an array ...
+        let nested = vec![0x81; MAX_CBOR_DEPTH + 2];
+        assert!(matches!(
+            Reader::new(&nested).value(0),
+            Err(Error::InvalidCbor(_))
+        ));
+    }
+}
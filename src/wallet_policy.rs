@@ -0,0 +1,253 @@
+use bitcoin::bip32::ChildNumber;
+use miniscript::{
+    descriptor::Wildcard, DescriptorPublicKey, MiniscriptKey, TranslateErr, TranslatePk as _,
+    Translator,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::descriptor::{self, Descriptors};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Unable to write the wallet as a wallet policy: {0}")]
+    InvalidDescriptor(#[from] descriptor::Error),
+
+    #[error("Wallet policy keys must be extended keys ending in /<a;b>/*, got {0}")]
+    UnsupportedKey(String),
+
+    #[error("Wallet policy template uses @{0}, but there are only {1} keys")]
+    MissingKey(usize, usize),
+
+    #[error("Invalid key placeholder in wallet policy template: {0}")]
+    InvalidPlaceholder(String),
+}
+
+/// A BIP388 wallet policy, a descriptor template with `@0`, `@1`... key placeholders and the
+/// list of keys they stand for
+///
+/// `wpkh(@0/**)` with the key `[817e7be0/84'/0'/0']xpub...` is the same wallet as
+/// `wpkh([817e7be0/84'/0'/0']xpub.../<0;1>/*)`
+#[derive(Debug, Clone, Serialize, Deserialize, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Object))]
+pub struct WalletPolicy {
+    pub template: String,
+    pub keys: Vec<String>,
+}
+
+impl WalletPolicy {
+    pub fn try_from_descriptors(descriptors: &Descriptors) -> Result<Self, Error> {
+        let multipath = descriptors.multipath_descriptor()?;
+
+        let mut translator = PlaceholderTranslator { keys: Vec::new() };
+        let template = multipath
+            .translate_pk(&mut translator)
+            .map_err(|error| match error {
+                TranslateErr::TranslatorErr(error) => error,
+                TranslateErr::OuterError(error) => {
+                    Error::InvalidDescriptor(descriptor::Error::InvalidDescriptorParse(error))
+                }
+            })?;
+
+        Ok(Self {
+            template: format!("{template:#}"),
+            keys: translator.keys,
+        })
+    }
+}
+
+impl WalletPolicy {
+    /// The descriptors the policy stands for, each `@n` replaced by its key and `/**` expanded to
+    /// `/<0;1>/*`
+    pub fn to_descriptors(&self) -> Result<Descriptors, Error> {
+        let mut line = String::with_capacity(self.template.len());
+        let mut rest = self.template.as_str();
+
+        while let Some((before, placeholder)) = rest.split_once('@') {
+            line.push_str(before);
+
+            let digits = placeholder
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(placeholder.len());
+
+            let invalid = || Error::InvalidPlaceholder(format!("@{placeholder}"));
+            let index = placeholder[..digits]
+                .parse::<usize>()
+                .map_err(|_| invalid())?;
+            let key = self
+                .keys
+                .get(index)
+                .ok_or(Error::MissingKey(index, self.keys.len()))?;
+
+            line.push_str(key);
+
+            rest = &placeholder[digits..];
+            match rest.strip_prefix("/**") {
+                Some(after) => {
+                    line.push_str("/<0;1>/*");
+                    rest = after;
+                }
+                None if rest.starts_with("/<") => (),
+                None => return Err(invalid()),
+            }
+        }
+
+        line.push_str(rest);
+        Ok(Descriptors::try_from_generated_line(&line)?)
+    }
+}
+
+/// Replaces each key with its `@n/**` placeholder, collecting the key origins and xpubs
+struct PlaceholderTranslator {
+    keys: Vec<String>,
+}
+
+impl Translator<DescriptorPublicKey, String, Error> for PlaceholderTranslator {
+    fn pk(&mut self, key: &DescriptorPublicKey) -> Result<String, Error> {
+        let unsupported = || Error::UnsupportedKey(key.to_string());
+
+        let DescriptorPublicKey::MultiXPub(xkey) = key else {
+            return Err(unsupported());
+        };
+
+        if xkey.wildcard != Wildcard::Unhardened {
+            return Err(unsupported());
+        }
+
+        let steps = match xkey.derivation_paths.paths().as_slice() {
+            [receive, change] => match (receive.as_ref(), change.as_ref()) {
+                (
+                    [ChildNumber::Normal { index: receive }],
+                    [ChildNumber::Normal { index: change }],
+                ) => (*receive, *change),
+                _ => return Err(unsupported()),
+            },
+            _ => return Err(unsupported()),
+        };
+
+        let key_info = match &xkey.origin {
+            Some((fingerprint, path)) if path.is_empty() => format!("[{fingerprint}]{}", xkey.xkey),
+            Some((fingerprint, path)) => format!("[{fingerprint}/{path}]{}", xkey.xkey),
+            None => xkey.xkey.to_string(),
+        };
+
+        let index = match self.keys.iter().position(|key| *key == key_info) {
+            Some(index) => index,
+            None => {
+                self.keys.push(key_info);
+                self.keys.len() - 1
+            }
+        };
+
+        match steps {
+            (0, 1) => Ok(format!("@{index}/**")),
+            (receive, change) => Ok(format!("@{index}/<{receive};{change}>/*")),
+        }
+    }
+
+    fn sha256(
+        &mut self,
+        hash: &<DescriptorPublicKey as MiniscriptKey>::Sha256,
+    ) -> Result<String, Error> {
+        Ok(hash.to_string())
+    }
+
+    fn hash256(
+        &mut self,
+        hash: &<DescriptorPublicKey as MiniscriptKey>::Hash256,
+    ) -> Result<String, Error> {
+        Ok(hash.to_string())
+    }
+
+    fn ripemd160(
+        &mut self,
+        hash: &<DescriptorPublicKey as MiniscriptKey>::Ripemd160,
+    ) -> Result<String, Error> {
+        Ok(hash.to_string())
+    }
+
+    fn hash160(
+        &mut self,
+        hash: &<DescriptorPublicKey as MiniscriptKey>::Hash160,
+    ) -> Result<String, Error> {
+        Ok(hash.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XPUB: &str = "xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM";
+
+    #[test]
+    fn test_single_sig_policy() {
+        let desc = Descriptors::try_from_line(&format!("wpkh([817e7be0/84h/0h/0h]{XPUB}/<0;1>/*)"));
+        let policy = desc.unwrap().to_wallet_policy().unwrap();
+
+        assert_eq!(policy.template, "wpkh(@0/**)");
+        assert_eq!(policy.keys, [format!("[817e7be0/84'/0'/0']{XPUB}")]);
+    }
+
+    #[test]
+    fn test_multisig_policy() {
+        let other = "xpub6CCKAvUTNursEnaJ8k1d27LfqEUzeAx2N9wFqYE3W1xh7nqgJEBEbLSSmohwDxzsSvcsYqiQqFzRvta65Njbe5o84bF5YXHFqfSH2Dkhonm";
+        let line = format!(
+            "wsh(sortedmulti(2,[817e7be0/48h/0h/0h/2h]{XPUB}/<0;1>/*,[90645a28/48h/0h/0h/2h]{other}/<2;3>/*))"
+        );
+
+        let policy = Descriptors::try_from_line(&line)
+            .unwrap()
+            .to_wallet_policy()
+            .unwrap();
+
+        assert_eq!(policy.template, "wsh(sortedmulti(2,@0/**,@1/<2;3>/*))");
+        assert_eq!(policy.keys.len(), 2);
+        assert!(policy.keys[1].starts_with("[90645a28/48'/0'/0'/2']xpub6CCK"));
+    }
+
+    #[test]
+    fn test_policy_round_trip() {
+        let other = "xpub6CCKAvUTNursEnaJ8k1d27LfqEUzeAx2N9wFqYE3W1xh7nqgJEBEbLSSmohwDxzsSvcsYqiQqFzRvta65Njbe5o84bF5YXHFqfSH2Dkhonm";
+        let lines = [
+            format!("wpkh([817e7be0/84h/0h/0h]{XPUB}/<0;1>/*)"),
+            format!(
+                "wsh(sortedmulti(2,[817e7be0/48h/0h/0h/2h]{XPUB}/<0;1>/*,[90645a28/48h/0h/0h/2h]{other}/<2;3>/*))"
+            ),
+        ];
+
+        for line in lines {
+            let desc = Descriptors::try_from_line(&line).unwrap();
+            let policy = desc.to_wallet_policy().unwrap();
+            assert_eq!(policy.to_descriptors().unwrap(), desc);
+        }
+    }
+
+    #[test]
+    fn test_invalid_policy() {
+        let policy = |template: &str| WalletPolicy {
+            template: template.to_string(),
+            keys: vec![format!("[817e7be0/84h/0h/0h]{XPUB}")],
+        };
+
+        assert!(matches!(
+            policy("wpkh(@1/**)").to_descriptors(),
+            Err(Error::MissingKey(1, 1))
+        ));
+        assert!(matches!(
+            policy("wpkh(@0/*)").to_descriptors(),
+            Err(Error::InvalidPlaceholder(_))
+        ));
+        assert!(matches!(
+            policy("wpkh(@/**)").to_descriptors(),
+            Err(Error::InvalidPlaceholder(_))
+        ));
+    }
+
+    #[test]
+    fn test_policy_needs_wildcard() {
+        let desc = Descriptors::try_from_line(&format!("wpkh([817e7be0/84h/0h/0h]{XPUB}/<0;1>)"));
+        let policy = desc.unwrap().to_wallet_policy();
+
+        assert!(matches!(policy, Err(Error::UnsupportedKey(_))));
+    }
+}
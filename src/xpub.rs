@@ -3,7 +3,11 @@ use std::str::FromStr as _;
 use bitcoin::{
     base58,
     bip32::{Fingerprint, Xpub as Bip32Xpub},
+    NetworkKind,
 };
+use serde::{Deserialize, Serialize};
+
+use crate::script_type::ScriptType;
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -13,13 +17,34 @@ pub enum Error {
     #[error("Invalid zpub: {0}")]
     InvalidZpub(#[from] base58::Error),
 
+    #[error("Invalid zpub, decoded to {0} bytes")]
+    InvalidZpubLength(usize),
+
     #[error("Invalid ypub: {0}")]
     InvalidYpubDecode(base58::Error),
 
     #[error("Invalid ypub: {0}")]
     InvalidYpubLength(usize),
 
-    #[error("Not an xpub, zpub or ypub, starts with: {0}")]
+    #[error("Invalid vpub: {0}")]
+    InvalidVpubDecode(base58::Error),
+
+    #[error("Invalid vpub, decoded to {0} bytes")]
+    InvalidVpubLength(usize),
+
+    #[error("Invalid upub: {0}")]
+    InvalidUpubDecode(base58::Error),
+
+    #[error("Invalid upub, decoded to {0} bytes")]
+    InvalidUpubLength(usize),
+
+    #[error("Invalid multisig Zpub, Ypub, Vpub or Upub: {0}")]
+    InvalidMultisigDecode(base58::Error),
+
+    #[error("Invalid multisig Zpub, Ypub, Vpub or Upub, decoded to {0} bytes")]
+    InvalidMultisigLength(usize),
+
+    #[error("Not an xpub, zpub, ypub, tpub, vpub or upub, starts with: {0}")]
     NotXpub(String),
 
     #[error("Too short, only {0} chars long")]
@@ -27,11 +52,34 @@ pub enum Error {
 
     #[error("Missing xpub")]
     MissingXpub,
+
+    #[error("Invalid fingerprint (xfp): {0}")]
+    InvalidFingerprint(String),
+
+    #[error("Expected a {expected:?} key, got a {got:?} one")]
+    WrongNetwork {
+        expected: NetworkKind,
+        got: NetworkKind,
+    },
+}
+
+/// Byte order of a master fingerprint (xfp) written as a hex string or an integer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ByteOrder {
+    /// The order the fingerprint is shown in descriptors and on devices, `817e7be0`
+    BigEndian,
+
+    /// Reversed, the 4 fingerprint bytes read as a little-endian integer (Electrum's `ckcc_xfp`)
+    LittleEndian,
+
+    /// Hex strings are big-endian, decimal integers are little-endian
+    Auto,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Xpub {
     xpub: String,
+    extended_key: Bip32Xpub,
     original_format: OriginalFormat,
 }
 
@@ -41,80 +89,323 @@ impl std::fmt::Display for Xpub {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, derive_more::Display)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, derive_more::Display)]
 pub enum OriginalFormat {
     Zpub,
     Ypub,
     Xpub,
+
+    /// Testnet `tpub`
+    Tpub,
+
+    /// Testnet `vpub`, native segwit
+    Vpub,
+
+    /// Testnet `upub`, nested segwit
+    Upub,
+
+    /// SLIP-132 `Zpub`, native segwit multisig
+    MultisigZpub,
+
+    /// SLIP-132 `Ypub`, nested segwit multisig
+    MultisigYpub,
+
+    /// SLIP-132 testnet `Vpub`, native segwit multisig
+    MultisigVpub,
+
+    /// SLIP-132 testnet `Upub`, nested segwit multisig
+    MultisigUpub,
 }
 
 impl Xpub {
+    /// Best guess at the master fingerprint of the wallet this key belongs to.
+    ///
+    /// Returns the parent fingerprint stored in the key, or the key's own fingerprint when it
+    /// has no parent (a master key). This is only the real master fingerprint for master keys
+    /// and their direct children, see [`Xpub::self_fingerprint`] for the key's own fingerprint.
+    pub fn master_fingerprint(&self) -> Fingerprint {
+        fingerprint_from_extended_key(&self.extended_key)
+    }
+
+    /// The master fingerprint, see [`Xpub::master_fingerprint`], which can't fail
+    #[deprecated(note = "use `Xpub::master_fingerprint` instead")]
     pub fn fingerprint(&self) -> Result<Fingerprint, Error> {
-        xpub_to_fingerprint(&self.xpub)
+        Ok(self.master_fingerprint())
+    }
+
+    /// The fingerprint of this key itself (first 4 bytes of the hash160 of its public key).
+    ///
+    /// Unlike [`Xpub::master_fingerprint`] this never looks at the parent fingerprint, use it
+    /// when matching against the parent fingerprint recorded in a child key.
+    pub fn self_fingerprint(&self) -> Fingerprint {
+        self.extended_key.fingerprint()
+    }
+
+    /// The SLIP-132 prefix the key had before it was converted to an `xpub`
+    pub fn original_format(&self) -> OriginalFormat {
+        self.original_format
+    }
+
+    /// Mainnet for `xpub` and its SLIP-132 forms, testnet (any test network) for `tpub`
+    pub fn network(&self) -> NetworkKind {
+        self.extended_key.network
+    }
+
+    /// How many derivations from the master key, `0` for a master key and `3` for an account key
+    pub fn depth(&self) -> u8 {
+        self.extended_key.depth
+    }
+
+    /// The parsed BIP32 key
+    pub fn extended_key(&self) -> Bip32Xpub {
+        self.extended_key
     }
 
     pub fn as_str(&self) -> &str {
         self.xpub.as_str()
     }
+
+    /// The BIP32 serialization of the key, always with the `xpub`/`tpub` version bytes
+    pub fn to_bytes(&self) -> [u8; 78] {
+        self.extended_key.encode()
+    }
 }
 
 impl TryFrom<&str> for Xpub {
     type Error = Error;
 
     fn try_from(xpub: &str) -> Result<Self, Self::Error> {
-        let (xpub, original_format) = match &xpub[..4] {
+        let prefix = xpub.get(..4).ok_or(Error::TooShort(xpub.len()))?;
+        let (xpub, original_format) = match prefix {
             "zpub" => (zpub_to_xpub(xpub)?, OriginalFormat::Zpub),
             "ypub" => (ypub_to_xpub(xpub)?, OriginalFormat::Ypub),
             "xpub" => (xpub.to_string(), OriginalFormat::Xpub),
+            "tpub" => (xpub.to_string(), OriginalFormat::Tpub),
+            "vpub" => (vpub_to_tpub(xpub)?, OriginalFormat::Vpub),
+            "upub" => (upub_to_tpub(xpub)?, OriginalFormat::Upub),
+            "Zpub" => (multisig_to_xpub(xpub)?, OriginalFormat::MultisigZpub),
+            "Ypub" => (multisig_to_xpub(xpub)?, OriginalFormat::MultisigYpub),
+            "Vpub" => (multisig_to_tpub(xpub)?, OriginalFormat::MultisigVpub),
+            "Upub" => (multisig_to_tpub(xpub)?, OriginalFormat::MultisigUpub),
             starting => return Err(Error::NotXpub(starting.to_string())),
         };
 
+        let extended_key = Bip32Xpub::from_str(&xpub)?;
+
         Ok(Self {
             xpub,
+            extended_key,
             original_format,
         })
     }
 }
 
 pub fn zpub_to_xpub(zpub: &str) -> Result<String, Error> {
-    let decoded = base58::decode_check(zpub)?;
+    // xpub version bytes
+    with_version(
+        zpub,
+        [0x04, 0x88, 0xB2, 0x1E],
+        Error::InvalidZpub,
+        Error::InvalidZpubLength,
+    )
+}
 
-    // Replace version bytes (first 4 bytes) with xpub version
-    let mut xpub_bytes = [0u8; 78];
-    xpub_bytes[0..4].copy_from_slice(&[0x04, 0x88, 0xB2, 0x1E]); // xpub version bytes
-    xpub_bytes[4..].copy_from_slice(&decoded[4..]);
+pub fn ypub_to_xpub(ypub: &str) -> Result<String, Error> {
+    // xpub version bytes
+    with_version(
+        ypub,
+        [0x04, 0x88, 0xB2, 0x1E],
+        Error::InvalidYpubDecode,
+        Error::InvalidYpubLength,
+    )
+}
 
-    // Re-encode as xpub
-    let xpub = base58::encode_check(&xpub_bytes);
+/// Convert a testnet native segwit `vpub` to a `tpub`
+pub fn vpub_to_tpub(vpub: &str) -> Result<String, Error> {
+    // tpub version bytes
+    with_version(
+        vpub,
+        [0x04, 0x35, 0x87, 0xCF],
+        Error::InvalidVpubDecode,
+        Error::InvalidVpubLength,
+    )
+}
 
-    Ok(xpub)
+/// Convert a testnet nested segwit `upub` to a `tpub`
+pub fn upub_to_tpub(upub: &str) -> Result<String, Error> {
+    // tpub version bytes
+    with_version(
+        upub,
+        [0x04, 0x35, 0x87, 0xCF],
+        Error::InvalidUpubDecode,
+        Error::InvalidUpubLength,
+    )
 }
 
-pub fn ypub_to_xpub(ypub: &str) -> Result<String, Error> {
-    let decoded = base58::decode_check(ypub).map_err(Error::InvalidYpubDecode)?;
+/// Convert an `xpub` to a native segwit `zpub`, the inverse of [`zpub_to_xpub`]
+pub fn xpub_to_zpub(xpub: &str) -> Result<String, Error> {
+    encode_slip132_for_network(xpub, NetworkKind::Main, ScriptType::P2wpkh)
+}
+
+/// Convert an `xpub` to a nested segwit `ypub`, the inverse of [`ypub_to_xpub`]
+pub fn xpub_to_ypub(xpub: &str) -> Result<String, Error> {
+    encode_slip132_for_network(xpub, NetworkKind::Main, ScriptType::P2shP2wpkh)
+}
+
+/// Convert a `tpub` to a testnet native segwit `vpub`, the inverse of [`vpub_to_tpub`]
+pub fn tpub_to_vpub(tpub: &str) -> Result<String, Error> {
+    encode_slip132_for_network(tpub, NetworkKind::Test, ScriptType::P2wpkh)
+}
+
+/// Convert a `tpub` to a testnet nested segwit `upub`, the inverse of [`upub_to_tpub`]
+pub fn tpub_to_upub(tpub: &str) -> Result<String, Error> {
+    encode_slip132_for_network(tpub, NetworkKind::Test, ScriptType::P2shP2wpkh)
+}
+
+fn encode_slip132_for_network(
+    key: &str,
+    network: NetworkKind,
+    script_type: ScriptType,
+) -> Result<String, Error> {
+    let extended_key = Bip32Xpub::from_str(key)?;
+
+    if extended_key.network != network {
+        return Err(Error::WrongNetwork {
+            expected: network,
+            got: extended_key.network,
+        });
+    }
+
+    Ok(slip132_encode(&extended_key, script_type))
+}
+
+/// Encode a key with the version bytes of `format`, the inverse of [`Xpub::try_from`]
+pub(crate) fn encode_with_format(xpub: &Bip32Xpub, format: OriginalFormat) -> String {
+    let version = match format {
+        OriginalFormat::Xpub => [0x04, 0x88, 0xB2, 0x1E],
+        OriginalFormat::Zpub => [0x04, 0xB2, 0x47, 0x46],
+        OriginalFormat::Ypub => [0x04, 0x9D, 0x7C, 0xB2],
+        OriginalFormat::Tpub => [0x04, 0x35, 0x87, 0xCF],
+        OriginalFormat::Vpub => [0x04, 0x5F, 0x1C, 0xF6],
+        OriginalFormat::Upub => [0x04, 0x4A, 0x52, 0x62],
+        OriginalFormat::MultisigZpub => [0x02, 0xAA, 0x7E, 0xD3],
+        OriginalFormat::MultisigYpub => [0x02, 0x95, 0xB4, 0x3F],
+        OriginalFormat::MultisigVpub => [0x02, 0x57, 0x54, 0x83],
+        OriginalFormat::MultisigUpub => [0x02, 0x42, 0x89, 0xEF],
+    };
+
+    let mut bytes = xpub.encode();
+    bytes[0..4].copy_from_slice(&version);
+
+    base58::encode_check(&bytes)
+}
+
+/// Encode a key with the SLIP-132 version bytes for a single sig script type
+///
+/// P2PKH and P2TR have no SLIP-132 prefix of their own, they are encoded as a plain `xpub` or
+/// `tpub`.
+pub(crate) fn slip132_encode(xpub: &Bip32Xpub, script_type: ScriptType) -> String {
+    let version = match (script_type, xpub.network) {
+        (ScriptType::P2wpkh, NetworkKind::Main) => [0x04, 0xB2, 0x47, 0x46], // zpub
+        (ScriptType::P2wpkh, NetworkKind::Test) => [0x04, 0x5F, 0x1C, 0xF6], // vpub
+        (ScriptType::P2shP2wpkh, NetworkKind::Main) => [0x04, 0x9D, 0x7C, 0xB2], // ypub
+        (ScriptType::P2shP2wpkh, NetworkKind::Test) => [0x04, 0x4A, 0x52, 0x62], // upub
+        (ScriptType::P2pkh | ScriptType::P2tr, _) => return xpub.to_string(),
+    };
+
+    let mut bytes = xpub.encode();
+    bytes[0..4].copy_from_slice(&version);
+
+    base58::encode_check(&bytes)
+}
+
+/// Convert a SLIP-132 multisig `Zpub` or `Ypub` to an `xpub`
+fn multisig_to_xpub(key: &str) -> Result<String, Error> {
+    multisig_with_version(key, [0x04, 0x88, 0xB2, 0x1E]) // xpub version bytes
+}
+
+/// Convert a SLIP-132 testnet multisig `Vpub` or `Upub` to a `tpub`
+fn multisig_to_tpub(key: &str) -> Result<String, Error> {
+    multisig_with_version(key, [0x04, 0x35, 0x87, 0xCF]) // tpub version bytes
+}
+
+fn multisig_with_version(key: &str, version: [u8; 4]) -> Result<String, Error> {
+    with_version(
+        key,
+        version,
+        Error::InvalidMultisigDecode,
+        Error::InvalidMultisigLength,
+    )
+}
+
+/// Re-encode an extended key with other version bytes, the errors name the prefix it had
+fn with_version(
+    key: &str,
+    version: [u8; 4],
+    decode_error: fn(base58::Error) -> Error,
+    length_error: fn(usize) -> Error,
+) -> Result<String, Error> {
+    let decoded = base58::decode_check(key).map_err(decode_error)?;
 
     if decoded.len() != 78 {
-        return Err(Error::InvalidYpubLength(decoded.len()));
+        return Err(length_error(decoded.len()));
     }
 
     let mut xpub_bytes = [0u8; 78];
     xpub_bytes.copy_from_slice(&decoded);
-    xpub_bytes[0..4].copy_from_slice(&[0x04, 0x88, 0xB2, 0x1E]); // xpub version bytes
+    xpub_bytes[0..4].copy_from_slice(&version);
 
-    // Re-encode as xpub
-    let xpub = base58::encode_check(&xpub_bytes);
-
-    Ok(xpub)
+    Ok(base58::encode_check(&xpub_bytes))
 }
 
 pub fn xpub_to_fingerprint(xpub: &str) -> Result<Fingerprint, Error> {
     let extended_pubkey = Bip32Xpub::from_str(xpub).map_err(Error::InvalidXpub)?;
-    let fingerprint = match extended_pubkey.parent_fingerprint.as_bytes() {
-        [0, 0, 0, 0] => extended_pubkey.fingerprint(),
-        _ => extended_pubkey.parent_fingerprint,
+    Ok(fingerprint_from_extended_key(&extended_pubkey))
+}
+
+/// Parse a master fingerprint given as 8 hex chars (optionally `0x` prefixed) or as a decimal
+/// integer, such as the `xfp` in Coldcard's generic JSON or the `ckcc_xfp` in Electrum files.
+///
+/// With [`ByteOrder::Auto`] an 8 char hex string is read big-endian and anything else is read
+/// as a little-endian decimal integer, so an all-digit 8 char string is treated as hex.
+pub fn parse_xfp(xfp: &str, order: ByteOrder) -> Result<Fingerprint, Error> {
+    let invalid = || Error::InvalidFingerprint(xfp.to_string());
+
+    let xfp = xfp.trim();
+    let hex = match xfp.strip_prefix("0x").or_else(|| xfp.strip_prefix("0X")) {
+        Some(hex) => Some(hex),
+        None if xfp.len() == 8 && xfp.chars().all(|c| c.is_ascii_hexdigit()) => Some(xfp),
+        None => None,
     };
 
-    Ok(fingerprint)
+    let (value, auto_order) = match hex {
+        Some(hex) if hex.len() == 8 => (u32::from_str_radix(hex, 16), ByteOrder::BigEndian),
+        Some(_) => return Err(invalid()),
+        None => (xfp.parse::<u32>(), ByteOrder::LittleEndian),
+    };
+
+    let value = value.map_err(|_| invalid())?;
+    let order = match order {
+        ByteOrder::Auto => auto_order,
+        order => order,
+    };
+
+    Ok(fingerprint_from_u32(value, order))
+}
+
+/// Fingerprint from its integer form, [`ByteOrder::Auto`] is treated as little-endian
+pub fn fingerprint_from_u32(xfp: u32, order: ByteOrder) -> Fingerprint {
+    match order {
+        ByteOrder::BigEndian => Fingerprint::from(xfp.to_be_bytes()),
+        ByteOrder::LittleEndian | ByteOrder::Auto => Fingerprint::from(xfp.to_le_bytes()),
+    }
+}
+
+fn fingerprint_from_extended_key(extended_pubkey: &Bip32Xpub) -> Fingerprint {
+    match extended_pubkey.parent_fingerprint.as_bytes() {
+        [0, 0, 0, 0] => extended_pubkey.fingerprint(),
+        _ => extended_pubkey.parent_fingerprint,
+    }
 }
 
 #[cfg(test)]
@@ -133,6 +424,63 @@ mod tests {
         assert_eq!(xpub.xpub, xpub_str);
     }
 
+    #[test]
+    fn test_truncated_zpub() {
+        let mut truncated = vec![0x04, 0xb2, 0x47, 0x46];
+        truncated.extend([0u8; 36]);
+        let zpub = base58::encode_check(&truncated);
+
+        assert!(matches!(
+            zpub_to_xpub(&zpub),
+            Err(Error::InvalidZpubLength(40))
+        ));
+        assert!(Xpub::try_from(zpub.as_str()).is_err());
+    }
+
+    #[test]
+    fn test_vpub_to_tpub() {
+        // BIP84 test vector, the testnet account of the `abandon ... about` wallet
+        let vpub = "vpub5Y6cjg78GGuNLsaPhmYsiw4gYX3HoQiRBiSwDaBXKUafCt9bNwWQiitDk5VZ5BVxYnQdwoTyXSs2JHRPAgjAvtbBrf8ZhDYe2jWAqvZVnsc";
+        let tpub = "tpubDC8msFGeGuwnKG9Upg7DM2b4DaRqg3CUZa5g8v2SRQ6K4NSkxUgd7HsL2XVWbVm39yBA4LAxysQAm397zwQSQoQgewGiYZqrA9DsP4zbQ1M";
+
+        assert_eq!(vpub_to_tpub(vpub).unwrap(), tpub);
+
+        let xpub = Xpub::try_from(vpub).unwrap();
+        assert_eq!(xpub.as_str(), tpub);
+        assert_eq!(xpub.original_format(), OriginalFormat::Vpub);
+        assert_eq!(xpub.network(), NetworkKind::Test);
+        assert_eq!(
+            xpub.master_fingerprint(),
+            Xpub::try_from(tpub).unwrap().master_fingerprint()
+        );
+
+        assert!(matches!(
+            vpub_to_tpub(&vpub[..vpub.len() - 1]),
+            Err(Error::InvalidVpubDecode(_))
+        ));
+    }
+
+    #[test]
+    fn test_upub_to_tpub() {
+        // BIP49 testnet account of the `abandon ... about` wallet
+        let upub = "upub5EFU65HtV5TeiSHmZZm7FUffBGy8UKeqp7vw43jYbvZPpoVsgU93oac7Wk3u6moKegAEWtGNF8DehrnHtv21XXEMYRUocHqguyjknFHYfgY";
+        let tpub = "tpubDD7tXK8KeQ3YY83yWq755fHY2JW8Ha8Q765tknUM5rSvjPcGWfUppDFMpQ1ScziKfW3ZNtZvAD7M3u7bSs7HofjTD3KP3YxPK7X6hwV8Rk2";
+
+        assert_eq!(upub_to_tpub(upub).unwrap(), tpub);
+
+        let xpub = Xpub::try_from(upub).unwrap();
+        assert_eq!(xpub.as_str(), tpub);
+        assert_eq!(xpub.original_format(), OriginalFormat::Upub);
+        assert_eq!(xpub.network(), NetworkKind::Test);
+
+        let mut truncated = vec![0x04, 0x4a, 0x52, 0x62];
+        truncated.extend([0u8; 36]);
+        assert!(matches!(
+            upub_to_tpub(&base58::encode_check(&truncated)),
+            Err(Error::InvalidUpubLength(40))
+        ));
+    }
+
     #[test]
     fn test_ypub_to_xpub() {
         let ypub = "ypub6X2aUb9NXbQM65mQy6oFECSB1CdSanwXHGTUcw7vt2LaAteuYtLoDQ6ao1fXDsenrZjgJKJyHvLypBBeo59cSKUivvwW8S6k7PVvQkVosxZ";
@@ -144,4 +492,114 @@ mod tests {
 
         assert_eq!(xpub.xpub, xpub_str);
     }
+
+    #[test]
+    fn test_xpub_to_slip132() {
+        let zpub = "zpub6rNrPrFwgm4wMBSysetK5tpLBS2HYT8TDKQA6amxFHKJUnQq8rNtc4JDfGYPbvF9wJyagPpG1Faqnfe3BB8XzKon8LwW9KkMWyAQ4RQHzB1";
+        assert_eq!(xpub_to_zpub(&zpub_to_xpub(zpub).unwrap()).unwrap(), zpub);
+
+        let ypub = "ypub6X2aUb9NXbQM65mQy6oFECSB1CdSanwXHGTUcw7vt2LaAteuYtLoDQ6ao1fXDsenrZjgJKJyHvLypBBeo59cSKUivvwW8S6k7PVvQkVosxZ";
+        assert_eq!(xpub_to_ypub(&ypub_to_xpub(ypub).unwrap()).unwrap(), ypub);
+
+        let vpub = "vpub5Y6cjg78GGuNLsaPhmYsiw4gYX3HoQiRBiSwDaBXKUafCt9bNwWQiitDk5VZ5BVxYnQdwoTyXSs2JHRPAgjAvtbBrf8ZhDYe2jWAqvZVnsc";
+        let tpub = vpub_to_tpub(vpub).unwrap();
+        assert_eq!(tpub_to_vpub(&tpub).unwrap(), vpub);
+
+        let upub = "upub5EFU65HtV5TeiSHmZZm7FUffBGy8UKeqp7vw43jYbvZPpoVsgU93oac7Wk3u6moKegAEWtGNF8DehrnHtv21XXEMYRUocHqguyjknFHYfgY";
+        assert_eq!(tpub_to_upub(&upub_to_tpub(upub).unwrap()).unwrap(), upub);
+
+        assert!(matches!(
+            xpub_to_zpub(&tpub),
+            Err(Error::WrongNetwork {
+                expected: NetworkKind::Main,
+                got: NetworkKind::Test
+            })
+        ));
+        assert!(matches!(xpub_to_zpub(zpub), Err(Error::InvalidXpub(_))));
+    }
+
+    #[test]
+    fn test_multisig_zpub_to_xpub() {
+        let zpub = "Zpub73GwX5zPFidJmkcMpKMHuyA8uE4Ykop3Xb3q1r3Vd49i6xyjuFmAhBA9TyVsAMU4An3ZYypNtTyLuqFoUQHV8ovSxp3uYjDMQhSGeRMzLB8";
+        let xpub_str = "xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM";
+        let xpub = Xpub::try_from(zpub).unwrap();
+
+        assert_eq!(xpub.as_str(), xpub_str);
+        assert_eq!(xpub.original_format(), OriginalFormat::MultisigZpub);
+    }
+
+    #[test]
+    fn test_testnet_multisig_to_tpub() {
+        // BIP48 testnet keys of the `abandon ... about` wallet, m/48h/1h/0h/2h and m/48h/1h/0h/1h
+        let vpub = "Vpub5n95dMZrDHj6SeBgJ1oz4Fae2N2eJNuWK3VTKDb2dzGpMFLUHLmtyDfen7AaQxwQ5mZnMyXdVrkEaoMLVTH8FmVBRVWPGFYWhmtDUGehGmq";
+        let xpub = Xpub::try_from(vpub).unwrap();
+
+        assert_eq!(xpub.as_str(), "tpubDFH9dgzveyD8zTbPUFuLrGmCydNvxehyNdUXKJAQN8x4aZ4j6UZqGfnqFrD4NqyaTVGKbvEW54tsvPTK2UoSbCC1PJY8iCNiwTL3RWZEheQ");
+        assert_eq!(xpub.original_format(), OriginalFormat::MultisigVpub);
+        assert_eq!(xpub.network(), NetworkKind::Test);
+
+        let upub = "Upub5TJpKgtw4cBcaAom7tyqG1yU3gSsjTVPkwWuR97vgrChHsT4S6M9d3BJ3jRmUgCUJZ58GUZhkWt6eGUVM7sdizaeuZqvC61TGRSP43VHvGm";
+        let xpub = Xpub::try_from(upub).unwrap();
+
+        assert_eq!(xpub.as_str(), "tpubDFH9dgzveyD8yHQb8VrpG8FYAuwcLMHMje2CCcbBo1FpaGzYVtJeYYxcYgRqSTta5utUFts8nPPHs9C2bqoxrey5jia6Dwf9mpwrPq7YvcJ");
+        assert_eq!(xpub.original_format(), OriginalFormat::MultisigUpub);
+        assert_eq!(xpub.network(), NetworkKind::Test);
+    }
+
+    #[test]
+    fn test_to_bytes() {
+        let zpub = "zpub6rNrPrFwgm4wMBSysetK5tpLBS2HYT8TDKQA6amxFHKJUnQq8rNtc4JDfGYPbvF9wJyagPpG1Faqnfe3BB8XzKon8LwW9KkMWyAQ4RQHzB1";
+        let xpub_str = "xpub6CiKnWv7PPyyeb4kCwK4fidKqVjPfD9TP6MiXnzBVGZYNanNdY3mMvywcrdDc6wK82jyBSd95vsk26QujnJWPrSaPfYeyW7NyX37HHGtfQM";
+
+        let bytes = Xpub::try_from(zpub).unwrap().to_bytes();
+        assert_eq!(bytes.to_vec(), base58::decode_check(xpub_str).unwrap());
+    }
+
+    #[test]
+    fn test_self_fingerprint_differs_from_master_fingerprint() {
+        let zpub = "zpub6rNrPrFwgm4wMBSysetK5tpLBS2HYT8TDKQA6amxFHKJUnQq8rNtc4JDfGYPbvF9wJyagPpG1Faqnfe3BB8XzKon8LwW9KkMWyAQ4RQHzB1";
+        let xpub = Xpub::try_from(zpub).unwrap();
+
+        assert_eq!(xpub.master_fingerprint().to_string(), "90645a28");
+        assert_eq!(xpub.self_fingerprint().to_string(), "8dfecfc3");
+    }
+
+    #[test]
+    fn test_parse_xfp() {
+        let expected = "817e7be0";
+
+        let parse = |xfp, order| parse_xfp(xfp, order).unwrap().to_string();
+        assert_eq!(parse("817E7BE0", ByteOrder::BigEndian), expected);
+        assert_eq!(parse("0x817e7be0", ByteOrder::Auto), expected);
+        assert_eq!(parse("E07B7E81", ByteOrder::LittleEndian), expected);
+
+        // electrum `ckcc_xfp` for the same wallet
+        assert_eq!(parse("3766189697", ByteOrder::Auto), expected);
+        assert_eq!(parse("3766189697", ByteOrder::LittleEndian), expected);
+        assert_eq!(parse("2172550112", ByteOrder::BigEndian), expected);
+
+        assert!(parse_xfp("817e7be", ByteOrder::Auto).is_err());
+        assert!(parse_xfp("0x817e7be0ff", ByteOrder::Auto).is_err());
+        assert!(parse_xfp("not an xfp", ByteOrder::Auto).is_err());
+    }
+
+    #[test]
+    fn test_master_key_fingerprints_match() {
+        let master = "xpub661MyMwAqRbcFFr2SGY3dUn7g8P9VKNZdKWL2Z2pZMEkBWH2D1KTcwTn7keZQCaScCx7BUDjHFJJHnzBvDgUFgNjYsQTRvo7LWfYEtt78Pb";
+        let xpub = Xpub::try_from(master).unwrap();
+
+        assert_eq!(xpub.master_fingerprint(), xpub.self_fingerprint());
+        assert_eq!(xpub.master_fingerprint().to_string(), "817e7be0");
+    }
+
+    #[test]
+    fn test_tpub_round_trip() {
+        let tpub = "tpubDC8msFGeGuwnKG9Upg7DM2b4DaRqg3CUZa5g8v2SRQ6K4NSkxUgd7HsL2XVWbVm39yBA4LAxysQAm397zwQSQoQgewGiYZqrA9DsP4zbQ1M";
+        let xpub = Xpub::try_from(tpub).unwrap();
+
+        assert_eq!(xpub.original_format(), OriginalFormat::Tpub);
+        assert_eq!(xpub.network(), NetworkKind::Test);
+        assert_eq!(xpub.as_str(), tpub);
+        assert_eq!(xpub.depth(), 3);
+    }
 }